@@ -12,7 +12,7 @@ fn main() {
     println!("シード: {:02X?}", seed);
     println!("タイプ: {}\n", payload_type);
 
-    let frame = pink072_wrap(payload, payload_type, &seed).unwrap();
+    let frame = pink072_wrap(payload, payload_type, seed).unwrap();
     println!("フレーム生成: {} bytes", frame.len());
 
     // Cover部分を抽出 (Header 32B の後、72x72x4 bytes)
@@ -0,0 +1,335 @@
+//! `test-vectors/` を生成する（`cargo run --example gen_test_vectors`）
+//!
+//! 出力は決定的なので、フォーマットを変えない限り再生成しても差分は出ない。
+//! 検証は `tests/vectors.rs` が行う。
+
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+use pink072::{
+    encode_png_rgba, encode_pnk, encode_pnk_with_layout, pink072_unwrap, pink072_wrap,
+    pink072_wrap_with_cover, sign_frame, KeyId, PinkError, PnkLayout, SigningKey, BLOCK_SIZE,
+    COVER_HEIGHT, COVER_LEN, COVER_WIDTH, FLAG_EXTERNAL_COVER, HEADER_LEN,
+};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+const HELLO: &[u8] = b"Hello, PINK-072!\n";
+
+fn main() {
+    let root = Path::new(env!("CARGO_MANIFEST_DIR")).join("test-vectors");
+    let _ = fs::remove_dir_all(root.join("valid"));
+    let _ = fs::remove_dir_all(root.join("invalid"));
+    fs::create_dir_all(root.join("valid")).unwrap();
+    fs::create_dir_all(root.join("invalid")).unwrap();
+
+    let mut file_payload = (9u16).to_le_bytes().to_vec();
+    file_payload.extend_from_slice(b"hello.txt");
+    file_payload.extend_from_slice(HELLO);
+    let bytes: Vec<u8> = (0..=255).collect();
+
+    let seed_a = [0x12, 0x34, 0x56, 0x78, 0x9A, 0xBC, 0xDE, 0xF0, 0x11];
+    let seed_b = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09];
+    let seed_c = [0xFF; 9];
+    let seed_d = [0xA5, 0x5A, 0x00, 0xC3, 0x3C, 0x99, 0x66, 0x81, 0x7E];
+
+    let hello = pink072_wrap(HELLO, 0, seed_b).unwrap();
+    let mut valid = vec![
+        valid_case(&root, "raw-empty", Some(seed_a), 0, &[], None),
+        valid_case(&root, "raw-hello", Some(seed_b), 0, HELLO, None),
+        valid_case(&root, "file-hello", Some(seed_c), 1, &file_payload, None),
+        valid_case(&root, "raw-bytes", Some(seed_d), 0, &bytes, None),
+    ];
+
+    // 外部カバー: 8x4のグラデーション（シードは無い）
+    let cover_rgba: Vec<u8> = (0..32u8)
+        .flat_map(|i| [i * 8, 0x40, 255 - i * 8, 0xFF])
+        .collect();
+    let external = pink072_wrap_with_cover(HELLO, 0, &cover_rgba, 8, 4).unwrap();
+    let mut case = valid_case(&root, "external-cover", None, 0, HELLO, Some(external));
+    case["decode_only"] = json!(false);
+    add_file(
+        &root,
+        &mut case,
+        "cover",
+        "valid/external-cover.cover",
+        &cover_rgba,
+    );
+    valid.push(case);
+
+    // 固定の鍵による署名（Ed25519の署名は決定的）
+    let signing_key = SigningKey::from_bytes(&[0x07; 32]);
+    let signed = sign_frame(&hello, &signing_key).unwrap();
+    let mut case = valid_case(&root, "signed-hello", Some(seed_b), 0, HELLO, Some(signed));
+    case["decode_only"] = json!(false);
+    case["signing_key"] = json!(hex(signing_key.as_bytes()));
+    case["key_id"] = json!(KeyId::of(&signing_key.verifying_key()).to_string());
+    valid.push(case);
+
+    // 未知のレコードを持つ拡張領域（デコーダは読み飛ばす）
+    let extension = [&[0xEE][..], &7u32.to_le_bytes(), b"unknown"].concat();
+    let mut extended = with_header(&hello, |h| {
+        h[24..28].copy_from_slice(&(extension.len() as u32).to_le_bytes())
+    });
+    extended.splice(HEADER_LEN..HEADER_LEN, extension);
+    valid.push(valid_case(
+        &root,
+        "extension-skipped",
+        Some(seed_b),
+        0,
+        HELLO,
+        Some(extended),
+    ));
+
+    // 旧形式（v1）: マジック無し、[0] バージョン、[1] タイプ、[2] ブロックサイズ、
+    // [3] フラグ、[4..12] ペイロード長
+    let mut legacy = vec![0u8; HEADER_LEN];
+    legacy[0] = 1;
+    legacy[2] = BLOCK_SIZE as u8;
+    legacy[4..12].copy_from_slice(&(HELLO.len() as u64).to_le_bytes());
+    legacy.extend_from_slice(&hello[HEADER_LEN..]);
+    valid.push(valid_case(
+        &root,
+        "legacy-v1",
+        Some(seed_b),
+        0,
+        HELLO,
+        Some(legacy.clone()),
+    ));
+
+    let cover = &hello[HEADER_LEN..HEADER_LEN + COVER_LEN];
+    let pnk = encode_pnk(&hello).unwrap();
+    let invalid = vec![
+        invalid_case(
+            &root,
+            "frame-truncated-header",
+            "frame",
+            "the header is cut short",
+            &hello[..HEADER_LEN - 1],
+        ),
+        invalid_case(
+            &root,
+            "frame-truncated-cover",
+            "frame",
+            "the cover ends before 72x72 RGBA pixels",
+            &hello[..HEADER_LEN + COVER_LEN - 1],
+        ),
+        invalid_case(
+            &root,
+            "frame-truncated-payload",
+            "frame",
+            "the last payload byte is missing",
+            &hello[..hello.len() - 1],
+        ),
+        invalid_case(
+            &root,
+            "frame-length-past-end",
+            "frame",
+            "payload length is one byte longer than the data",
+            &with_header(&hello, |h| {
+                h[8..16].copy_from_slice(&(HELLO.len() as u64 + 1).to_le_bytes())
+            }),
+        ),
+        invalid_case(
+            &root,
+            "frame-length-overflow",
+            "frame",
+            "payload length is u64::MAX",
+            &with_header(&hello, |h| h[8..16].fill(0xFF)),
+        ),
+        invalid_case(
+            &root,
+            "frame-wrong-version",
+            "frame",
+            "magic PINK with version 3",
+            &with_header(&hello, |h| h[4] = 3),
+        ),
+        invalid_case(
+            &root,
+            "frame-unknown-legacy-version",
+            "frame",
+            "no magic and a first byte other than 1",
+            &with_header(&legacy, |h| h[0] = 7),
+        ),
+        invalid_case(
+            &root,
+            "frame-extension-past-end",
+            "frame",
+            "extension length reaches past the end of the frame",
+            &with_header(&hello, |h| {
+                h[24..28].copy_from_slice(&1024u32.to_le_bytes())
+            }),
+        ),
+        invalid_case(
+            &root,
+            "frame-external-cover-zero-size",
+            "frame",
+            "external cover flag with a 0x0 cover",
+            &with_header(&hello, |h| h[7] = FLAG_EXTERNAL_COVER),
+        ),
+        invalid_case(
+            &root,
+            "pnk-not-png",
+            "pnk",
+            "a bare frame without the PNG",
+            &hello,
+        ),
+        invalid_case(
+            &root,
+            "pnk-without-frame",
+            "pnk",
+            "only the cover PNG, nothing after IEND",
+            &encode_png_rgba(cover, COVER_WIDTH, COVER_HEIGHT),
+        ),
+        invalid_case(
+            &root,
+            "pnk-truncated-png",
+            "pnk",
+            "the file ends inside IDAT",
+            &pnk[..100],
+        ),
+        invalid_case(
+            &root,
+            "pnk-bad-crc",
+            "pnk",
+            "one byte of IHDR is changed",
+            &{
+                let mut pnk = pnk.clone();
+                pnk[16] ^= 0x01;
+                pnk
+            },
+        ),
+        invalid_case(
+            &root,
+            "pnk-truncated-frame",
+            "pnk",
+            "the last payload byte after IEND is missing",
+            &pnk[..pnk.len() - 1],
+        ),
+        invalid_case(
+            &root,
+            "pnk-wrong-version",
+            "pnk",
+            "the frame after IEND has version 3",
+            &{
+                let mut pnk = pnk.clone();
+                let png_len = pnk.len() - hello.len();
+                pnk[png_len + 4] = 3;
+                pnk
+            },
+        ),
+    ];
+
+    let manifest = json!({
+        "format": "pink072-test-vectors",
+        "version": 1,
+        "valid": valid,
+        "invalid": invalid,
+    });
+    let mut text = serde_json::to_string_pretty(&manifest).unwrap();
+    text.push('\n');
+    fs::write(root.join("manifest.json"), text).unwrap();
+    println!("test-vectors/ を生成しました");
+}
+
+/// 正しいフレームのケースを書き出す
+///
+/// `frame` を渡すと `pink072_wrap` の代わりに使い、`decode_only` にする。
+/// エンコーダが作れるフレームなら、呼び出し側で `decode_only` を戻して再現に要る情報を足す。
+fn valid_case(
+    root: &Path,
+    name: &str,
+    seed9: Option<[u8; 9]>,
+    payload_type: u8,
+    payload: &[u8],
+    frame: Option<Vec<u8>>,
+) -> Value {
+    let decode_only = frame.is_some();
+    let frame =
+        frame.unwrap_or_else(|| pink072_wrap(payload, payload_type, seed9.expect("seed")).unwrap());
+    assert_eq!(
+        pink072_unwrap(&frame).unwrap(),
+        (payload_type, payload.to_vec())
+    );
+
+    let cover = &frame[cover_range(&frame)];
+    let files = [
+        ("payload", format!("valid/{name}.payload"), payload.to_vec()),
+        ("frame", format!("valid/{name}.frame"), frame.clone()),
+        (
+            "pnk",
+            format!("valid/{name}.pnk"),
+            encode_pnk(&frame).unwrap(),
+        ),
+        (
+            "pnk_chunk",
+            format!("valid/{name}.chunk.pnk"),
+            encode_pnk_with_layout(&frame, PnkLayout::Chunk).unwrap(),
+        ),
+    ];
+
+    let mut case = json!({
+        "name": name,
+        "seed": seed9.map(|seed9| hex(&seed9)),
+        "payload_type": payload_type,
+        "decode_only": decode_only,
+        "cover_sha256": hex(&Sha256::digest(cover)),
+    });
+    for (key, path, data) in files {
+        add_file(root, &mut case, key, &path, &data);
+    }
+    case
+}
+
+fn add_file(root: &Path, case: &mut Value, key: &str, path: &str, data: &[u8]) {
+    fs::write(root.join(path), data).unwrap();
+    case[key] = json!(path);
+}
+
+/// カバーの範囲: 拡張領域の後ろから幅 x 高さ x 4 バイト
+///
+/// v2ヘッダは `[24..28]` が拡張領域の長さ、外部カバーなら `[16..24]` が幅・高さ。
+/// 旧形式（v1）は拡張領域が無く、カバーは常に72x72。
+fn cover_range(frame: &[u8]) -> Range<usize> {
+    let u32_at = |at: usize| u32::from_le_bytes(frame[at..at + 4].try_into().unwrap()) as usize;
+    if !frame.starts_with(b"PINK") {
+        return HEADER_LEN..HEADER_LEN + COVER_LEN;
+    }
+    let start = HEADER_LEN + u32_at(24);
+    let len = if frame[7] & FLAG_EXTERNAL_COVER != 0 {
+        u32_at(16) * u32_at(20) * 4
+    } else {
+        COVER_LEN
+    };
+    start..start + len
+}
+
+/// 不正な入力のケースを書き出す（期待するエラーは現在の実装から求める）
+fn invalid_case(root: &Path, name: &str, input: &str, description: &str, data: &[u8]) -> Value {
+    let error = match input {
+        "frame" => pink072_unwrap(data).unwrap_err(),
+        _ => pink072::decode_pnk(data)
+            .and_then(|frame| pink072_unwrap(&frame))
+            .unwrap_err(),
+    };
+    let path = format!("invalid/{name}.{input}");
+    fs::write(root.join(&path), data).unwrap();
+    json!({
+        "name": name,
+        "input": input,
+        "description": description,
+        "file": path,
+        "error": PinkError::code(&error),
+    })
+}
+
+fn with_header(frame: &[u8], edit: impl FnOnce(&mut [u8])) -> Vec<u8> {
+    let mut frame = frame.to_vec();
+    edit(&mut frame[..HEADER_LEN]);
+    frame
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
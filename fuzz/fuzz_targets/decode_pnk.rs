@@ -0,0 +1,37 @@
+//! `decode_pnk`・他の画像形式・ストリームのデコーダ
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pink072::{decode_carrier, decode_pnk, pink072_unwrap, PnkDecoder};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(frame) = decode_pnk(data) {
+        let _ = pink072_unwrap(&frame);
+    }
+    let _ = decode_carrier(data);
+
+    // 2つに分けて流しても一度に流しても、結果は変わらない
+    let mut whole = PnkDecoder::new();
+    let whole_events = whole.feed(data);
+    let mut split = PnkDecoder::new();
+    let (head, tail) = data.split_at(data.len() / 2);
+    let split_events = split.feed(head).and_then(|mut events| {
+        events.extend(split.feed(tail)?);
+        Ok(events)
+    });
+    if let (Ok(a), Ok(b)) = (&whole_events, &split_events) {
+        let payload = |events: &[pink072::PnkEvent]| -> Vec<u8> {
+            events
+                .iter()
+                .filter_map(|e| match e {
+                    pink072::PnkEvent::PayloadChunk(chunk) => Some(chunk.as_slice()),
+                    _ => None,
+                })
+                .flatten()
+                .copied()
+                .collect()
+        };
+        assert_eq!(payload(a), payload(b));
+    }
+    assert_eq!(whole_events.is_ok(), split_events.is_ok());
+});
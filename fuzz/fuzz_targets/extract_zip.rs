@@ -0,0 +1,30 @@
+//! ZIPの展開（展開先の外に書き込まないこと）
+#![no_main]
+
+use std::fs;
+use std::path::Path;
+
+use libfuzzer_sys::fuzz_target;
+use pink072::fuzzing::extract_zip_to_folder;
+
+fuzz_target!(|data: &[u8]| {
+    let root = std::env::temp_dir().join(format!("pink072-fuzz-{}", std::process::id()));
+    let out = root.join("out");
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&out).unwrap();
+
+    if let Ok(names) = extract_zip_to_folder(data, &out) {
+        for name in names {
+            assert!(!Path::new(&name).is_absolute(), "{name}");
+        }
+    }
+
+    // `out` の隣には何も作られていない
+    let siblings: Vec<_> = fs::read_dir(&root)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name())
+        .collect();
+    assert_eq!(siblings, ["out"]);
+
+    let _ = fs::remove_dir_all(&root);
+});
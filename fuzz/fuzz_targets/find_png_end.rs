@@ -0,0 +1,16 @@
+//! `find_png_end` とPNGのチャンク走査
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pink072::{find_png_end, PngChunks};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(end) = find_png_end(data) {
+        assert!(end <= data.len());
+    }
+    if let Ok(chunks) = PngChunks::new(data) {
+        for chunk in chunks.flatten() {
+            assert!(chunk.end() <= data.len());
+        }
+    }
+});
@@ -0,0 +1,11 @@
+//! ファイルのペイロード（`[名前の長さ][名前][データ]`）
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pink072::fuzzing::parse_file_payload;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok((name, file_data)) = parse_file_payload(data) {
+        assert_eq!(2 + name.len() + file_data.len(), data.len());
+    }
+});
@@ -0,0 +1,13 @@
+//! ZIPのエントリ一覧と、一覧にある各エントリの読み出し
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pink072::{list_zip_entries, read_zip_entry};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(entries) = list_zip_entries(data) {
+        for entry in entries {
+            let _ = read_zip_entry(data, &entry.path);
+        }
+    }
+});
@@ -0,0 +1,13 @@
+//! `pink072_unwrap` と署名・暗号化のヘッダ読み取り
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use pink072::{frame_key_id, frame_recipients, pink072_unwrap};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok((_, payload)) = pink072_unwrap(data) {
+        assert!(payload.len() <= data.len());
+    }
+    let _ = frame_key_id(data);
+    let _ = frame_recipients(data);
+});
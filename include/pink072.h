@@ -0,0 +1,112 @@
+/* Generated by cbindgen from src/ffi.rs. Do not edit by hand. */
+
+#ifndef PINK072_H
+#define PINK072_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+#define PINK072_OK 0
+
+#define PINK072_ERR_SEED_LENGTH 1
+
+#define PINK072_ERR_BUFFER_TOO_SMALL 2
+
+#define PINK072_ERR_FRAME_TOO_SMALL 3
+
+#define PINK072_ERR_PAYLOAD_LENGTH_OVERFLOW 4
+
+#define PINK072_ERR_TRUNCATED_FRAME 5
+
+#define PINK072_ERR_INVALID_FORMAT 6
+
+#define PINK072_ERR_COVER_MISMATCH 7
+
+#define PINK072_ERR_INVALID_COVER 8
+
+#define PINK072_ERR_INVALID_SEED 9
+
+#define PINK072_ERR_ENTROPY 10
+
+#define PINK072_ERR_PNG_CRC_MISMATCH 11
+
+#define PINK072_ERR_PNG_MISSING_IHDR 12
+
+#define PINK072_ERR_PNG_CHUNK_OVERFLOW 13
+
+#define PINK072_ERR_PNG_MISSING_IEND 14
+
+#define PINK072_ERR_UNSUPPORTED_PNG 15
+
+#define PINK072_ERR_CORRUPT_IMAGE_DATA 16
+
+#define PINK072_ERR_UNSUPPORTED_VERSION 17
+
+#define PINK072_ERR_MISSING_IMAGE_END 18
+
+#define PINK072_ERR_UNSIGNED 19
+
+#define PINK072_ERR_UNTRUSTED_KEY 20
+
+#define PINK072_ERR_BAD_SIGNATURE 21
+
+#define PINK072_ERR_ENCRYPTED 22
+
+#define PINK072_ERR_NO_RECIPIENTS 23
+
+#define PINK072_ERR_INVALID_RECIPIENT_KEY 24
+
+#define PINK072_ERR_NOT_RECIPIENT 25
+
+#define PINK072_ERR_DECRYPTION_FAILED 26
+
+#define PINK072_ERR_ENCRYPTION_FAILED 27
+
+#define PINK072_ERR_NULL_POINTER 100
+
+#define PINK072_ERR_INVALID_ARGUMENT 101
+
+#define PINK072_ERR_IO 102
+
+#define PINK072_ERR_PANIC 103
+
+typedef struct Pink072Buffer {
+  uint8_t *data;
+  uintptr_t len;
+} Pink072Buffer;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+int32_t pink072_wrap(const uint8_t *payload,
+                     uintptr_t payload_len,
+                     uint8_t payload_type,
+                     const uint8_t *seed9,
+                     struct Pink072Buffer *out_frame);
+
+int32_t pink072_unwrap(const uint8_t *frame,
+                       uintptr_t frame_len,
+                       uint8_t *out_payload_type,
+                       struct Pink072Buffer *out_payload);
+
+int32_t pink072_encode_pnk(const uint8_t *frame, uintptr_t frame_len, struct Pink072Buffer *out_pnk);
+
+int32_t pink072_decode_pnk(const uint8_t *pnk, uintptr_t pnk_len, struct Pink072Buffer *out_frame);
+
+int32_t pink072_encode_file(const char *input_path, const char *output_path, const uint8_t *seed9);
+
+int32_t pink072_decode_file(const char *input_path, const char *output_dir);
+
+void pink072_buffer_free(struct Pink072Buffer buffer);
+
+const char *pink072_error_message(int32_t code);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* PINK072_H */
@@ -0,0 +1,189 @@
+//! tokioを使う非同期版のファイル/フォルダのエンコード・デコード（`async` フィーチャー）
+//!
+//! ファイルの読み書きは `tokio::fs`、ZIPの作成・展開は `spawn_blocking` で行うので、
+//! 非同期ランタイムのワーカースレッドを止めない。tokioのランタイム上で呼ぶこと。
+
+use std::io;
+use std::path::Path;
+
+use tokio::fs::{self, File};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufWriter};
+use tokio::task;
+
+use crate::constants::{COVER_HEIGHT, COVER_LEN, COVER_WIDTH, HEADER_LEN};
+use crate::core::write_header;
+use crate::cover::generate_cover;
+use crate::file::{
+    build_file_payload, create_zip_from_folder, extract_zip_to_folder, open_frame,
+    output_file_path, parse_file_payload, wrap_frame, DecodeOptions, EncodeOptions,
+    PAYLOAD_TYPE_FILE, PAYLOAD_TYPE_RAW, PAYLOAD_TYPE_ZIP,
+};
+use crate::{encode_png_rgba, encode_pnk, Seed9};
+
+/// 単一ファイルをPNKにエンコード（`encode_file` の非同期版）
+///
+/// 入力ファイルはメモリに読み込まず、`write_pnk_async` で出力へ流し込む。
+pub async fn encode_file_async(
+    input_path: &Path,
+    output_path: &Path,
+    seed9: impl Into<Seed9>,
+) -> io::Result<()> {
+    encode_file_async_with(input_path, output_path, seed9, &EncodeOptions::default()).await
+}
+
+/// オプションを指定して単一ファイルをPNKにエンコード（`encode_file_with` の非同期版）
+///
+/// 暗号化するときはファイル全体を読み込んでからフレームを生成する。
+pub async fn encode_file_async_with(
+    input_path: &Path,
+    output_path: &Path,
+    seed9: impl Into<Seed9>,
+    options: &EncodeOptions<'_>,
+) -> io::Result<()> {
+    let seed9 = seed9.into();
+    let file_name = input_path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid file name"))?;
+
+    if !options.recipients.is_empty() {
+        let file_data = fs::read(input_path).await?;
+        let payload = build_file_payload(file_name, &file_data);
+        let frame = wrap_frame(&payload, PAYLOAD_TYPE_FILE, seed9, options)?;
+        let pnk = encode_pnk(&frame).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        return fs::write(output_path, pnk).await;
+    }
+
+    let input = File::open(input_path).await?;
+    let data_len = input.metadata().await?.len();
+    let prefix = build_file_payload(file_name, &[]);
+    let payload = prefix.as_slice().chain(input);
+
+    let mut output = BufWriter::new(File::create(output_path).await?);
+    let payload_len = prefix.len() as u64 + data_len;
+    write_pnk_async(payload, payload_len, PAYLOAD_TYPE_FILE, seed9, &mut output).await
+}
+
+/// フォルダをZIP化してPNKにエンコード（`encode_folder` の非同期版）
+pub async fn encode_folder_async(
+    input_path: &Path,
+    output_path: &Path,
+    seed9: impl Into<Seed9>,
+) -> io::Result<()> {
+    encode_folder_async_with(input_path, output_path, seed9, &EncodeOptions::default()).await
+}
+
+/// オプションを指定してフォルダをZIP化してPNKにエンコード（`encode_folder_with` の非同期版）
+pub async fn encode_folder_async_with(
+    input_path: &Path,
+    output_path: &Path,
+    seed9: impl Into<Seed9>,
+    options: &EncodeOptions<'_>,
+) -> io::Result<()> {
+    let seed9 = seed9.into();
+    let folder = input_path.to_path_buf();
+    let zip_data = blocking(move || create_zip_from_folder(&folder)).await?;
+
+    let frame = wrap_frame(&zip_data, PAYLOAD_TYPE_ZIP, seed9, options)?;
+    let pnk = encode_pnk(&frame).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    fs::write(output_path, pnk).await
+}
+
+/// PNKを自動判定してデコード（`decode_auto` の非同期版）
+pub async fn decode_auto_async(input_path: &Path, output_dir: &Path) -> io::Result<Vec<String>> {
+    decode_auto_async_with(input_path, output_dir, &DecodeOptions::default()).await
+}
+
+/// オプションを指定してPNKを自動判定してデコード（`decode_auto_with` の非同期版）
+pub async fn decode_auto_async_with(
+    input_path: &Path,
+    output_dir: &Path,
+    options: &DecodeOptions<'_>,
+) -> io::Result<Vec<String>> {
+    let pnk_data = fs::read(input_path).await?;
+    let (payload_type, payload) = open_frame(&pnk_data, options)?;
+    drop(pnk_data);
+
+    match payload_type {
+        PAYLOAD_TYPE_RAW => {
+            fs::create_dir_all(output_dir).await?;
+            fs::write(output_dir.join("data.bin"), &payload).await?;
+            Ok(vec!["data.bin".to_string()])
+        }
+        PAYLOAD_TYPE_FILE => {
+            let (file_name, file_data) = parse_file_payload(&payload)?;
+            let output_file = output_file_path(output_dir, &file_name)?;
+            fs::create_dir_all(output_dir).await?;
+            fs::write(output_file, file_data).await?;
+            Ok(vec![file_name])
+        }
+        PAYLOAD_TYPE_ZIP => {
+            let output_dir = output_dir.to_path_buf();
+            blocking(move || extract_zip_to_folder(&payload, &output_dir)).await
+        }
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unknown payload type: {payload_type}"),
+        )),
+    }
+}
+
+/// `reader` からPNKを読み、`(payload_type, payload)` を取り出す（キャリアの形式は自動判定）
+pub async fn read_payload_async<R: AsyncRead + Unpin>(
+    mut reader: R,
+    options: &DecodeOptions<'_>,
+) -> io::Result<(u8, Vec<u8>)> {
+    let mut pnk_data = Vec::new();
+    reader.read_to_end(&mut pnk_data).await?;
+    open_frame(&pnk_data, options)
+}
+
+/// `payload` から `payload_len` バイトを読みながら、PNKを `writer` に書き出す
+///
+/// 出力は `encode_pnk(&pink072_wrap(..))` と同じバイト列だが、ペイロード全体を
+/// メモリに載せないので、HTTPのレスポンスボディなどへ大きなデータをそのまま流せる。
+/// `payload` が `payload_len` より短ければ `UnexpectedEof`。最後に `writer` をフラッシュする。
+pub async fn write_pnk_async<R, W>(
+    payload: R,
+    payload_len: u64,
+    payload_type: u8,
+    seed9: impl Into<Seed9>,
+    writer: &mut W,
+) -> io::Result<()>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin + ?Sized,
+{
+    let len = usize::try_from(payload_len).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            crate::PinkError::PayloadLengthOverflow,
+        )
+    })?;
+
+    let mut head = vec![0u8; HEADER_LEN + COVER_LEN];
+    write_header(&mut head, payload_type, 0, len);
+    generate_cover(&mut head[HEADER_LEN..], &seed9.into());
+
+    let png = encode_png_rgba(&head[HEADER_LEN..], COVER_WIDTH, COVER_HEIGHT);
+    writer.write_all(&png).await?;
+    writer.write_all(&head).await?;
+
+    let copied = tokio::io::copy(&mut payload.take(payload_len), writer).await?;
+    if copied != payload_len {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            format!("payload ended after {copied} of {payload_len} bytes"),
+        ));
+    }
+    writer.flush().await
+}
+
+/// 同期のファイル処理をブロッキング用のスレッドで実行する
+async fn blocking<T, F>(f: F) -> io::Result<T>
+where
+    F: FnOnce() -> io::Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    task::spawn_blocking(f).await.map_err(io::Error::other)?
+}
@@ -0,0 +1,115 @@
+//! 最小限のBMPエンコーダ（24bit、無圧縮）と終端検出
+
+use alloc::vec::Vec;
+
+use crate::error::PinkError;
+
+/// BMPのシグネチャ
+pub(crate) const BMP_SIGNATURE: [u8; 2] = *b"BM";
+
+/// ファイルヘッダ（14バイト）+ BITMAPINFOHEADER（40バイト）
+const BMP_HEADER_LEN: usize = 14 + 40;
+
+/// 72dpi（ピクセル/メートル）
+const PIXELS_PER_METER: u32 = 2835;
+
+/// 任意サイズのRGBAを24bitのBMPにエンコード
+///
+/// アルファチャンネルは無視する。幅・高さは 1〜2^31-1（範囲外は `InvalidCover`）。
+pub fn encode_bmp_rgba(rgba: &[u8], width: usize, height: usize) -> Result<Vec<u8>, PinkError> {
+    if width == 0 || height == 0 || width > i32::MAX as usize || height > i32::MAX as usize {
+        return Err(PinkError::InvalidCover);
+    }
+    let stride = width
+        .checked_mul(3)
+        .map(|row| (row + 3) & !3)
+        .ok_or(PinkError::InvalidCover)?;
+    let image_len = stride
+        .checked_mul(height)
+        .filter(|&len| len <= u32::MAX as usize - BMP_HEADER_LEN)
+        .ok_or(PinkError::InvalidCover)?;
+    debug_assert_eq!(rgba.len(), width * height * 4);
+
+    let mut out = Vec::with_capacity(BMP_HEADER_LEN + image_len);
+    out.extend_from_slice(&BMP_SIGNATURE);
+    out.extend_from_slice(&((BMP_HEADER_LEN + image_len) as u32).to_le_bytes());
+    out.extend_from_slice(&[0, 0, 0, 0]);
+    out.extend_from_slice(&(BMP_HEADER_LEN as u32).to_le_bytes());
+
+    out.extend_from_slice(&40u32.to_le_bytes());
+    out.extend_from_slice(&(width as i32).to_le_bytes());
+    out.extend_from_slice(&(height as i32).to_le_bytes()); // 正ならボトムアップ
+    out.extend_from_slice(&1u16.to_le_bytes());
+    out.extend_from_slice(&24u16.to_le_bytes());
+    out.extend_from_slice(&0u32.to_le_bytes()); // BI_RGB
+    out.extend_from_slice(&(image_len as u32).to_le_bytes());
+    out.extend_from_slice(&PIXELS_PER_METER.to_le_bytes());
+    out.extend_from_slice(&PIXELS_PER_METER.to_le_bytes());
+    out.extend_from_slice(&[0; 8]);
+
+    for row in rgba.chunks_exact(width * 4).rev() {
+        let start = out.len();
+        for pixel in row.chunks_exact(4) {
+            out.extend_from_slice(&[pixel[2], pixel[1], pixel[0]]);
+        }
+        out.resize(start + stride, 0);
+    }
+    Ok(out)
+}
+
+/// BMPの終端（ピクセル配列の直後）を探す
+///
+/// ファイルヘッダのファイルサイズは信用せず、ピクセル配列の開始位置と
+/// 情報ヘッダの幅・高さ・ビット数から求める。圧縮形式では画像サイズの欄を使う。
+pub fn find_bmp_end(data: &[u8]) -> Result<usize, PinkError> {
+    if !data.starts_with(&BMP_SIGNATURE) {
+        return Err(PinkError::InvalidFormat);
+    }
+    let read_u16 = |at: usize| -> Result<u32, PinkError> {
+        let bytes = data.get(at..at + 2).ok_or(PinkError::MissingImageEnd)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]) as u32)
+    };
+    let read_u32 = |at: usize| -> Result<u32, PinkError> {
+        let bytes = data.get(at..at + 4).ok_or(PinkError::MissingImageEnd)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    };
+
+    let pixels_at = read_u32(10)? as u64;
+    let info_len = read_u32(14)?;
+
+    // (幅, 高さ, ビット数, 圧縮形式, 画像サイズ)
+    let (width, height, bit_count, compression, image_len) = if info_len == 12 {
+        // BITMAPCOREHEADER（OS/2）
+        (read_u16(18)?, read_u16(20)?, read_u16(24)?, 0, 0)
+    } else if info_len >= 40 {
+        (
+            read_u32(18)?,
+            (read_u32(22)? as i32).unsigned_abs(), // 負ならトップダウン
+            read_u16(28)?,
+            read_u32(30)?,
+            read_u32(34)?,
+        )
+    } else {
+        return Err(PinkError::InvalidFormat);
+    };
+
+    let pixels_len = match compression {
+        // BI_RGB / BI_BITFIELDS / BI_ALPHABITFIELDS
+        0 | 3 | 6 => {
+            let stride = (width as u64 * bit_count as u64).div_ceil(32) * 4;
+            stride
+                .checked_mul(height as u64)
+                .ok_or(PinkError::InvalidFormat)?
+        }
+        _ if image_len > 0 => image_len as u64,
+        _ => return Err(PinkError::InvalidFormat),
+    };
+
+    let end = pixels_at
+        .checked_add(pixels_len)
+        .ok_or(PinkError::InvalidFormat)?;
+    if end > data.len() as u64 {
+        return Err(PinkError::MissingImageEnd);
+    }
+    Ok(end as usize)
+}
@@ -0,0 +1,239 @@
+//! PNG以外の画像形式をカバーとして使うキャリア
+//!
+//! 出力: `[画像(カバー)][PINK-072フレーム全体]`。
+//! 画像の終端は形式ごとに求め、その後ろをフレームとして扱う。
+
+use alloc::{borrow::Cow, vec::Vec};
+
+use crate::bmp::{encode_bmp_rgba, find_bmp_end, BMP_SIGNATURE};
+use crate::constants::FRAME_MAGIC;
+use crate::core::FrameHeader;
+use crate::error::PinkError;
+use crate::gif::{encode_gif_rgba, find_gif_end, GIF_SIGNATURE};
+use crate::jpeg::{encode_jpeg_rgba, find_jpeg_end, JPEG_DEFAULT_QUALITY, JPEG_SIGNATURE};
+use crate::png::{
+    decode_pnk, encode_png_rgba, encode_pnk_with_layout, find_png_end, PnkLayout, PNG_SIGNATURE,
+};
+use crate::webp::{encode_webp_rgba, find_webp_end};
+
+/// キャリアの画像形式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CarrierFormat {
+    Png,
+    Jpeg,
+    Gif,
+    Bmp,
+    WebP,
+}
+
+impl CarrierFormat {
+    /// 先頭のシグネチャから形式を判定する
+    pub fn detect(data: &[u8]) -> Option<Self> {
+        if data.starts_with(&PNG_SIGNATURE) {
+            Some(CarrierFormat::Png)
+        } else if data.starts_with(&JPEG_SIGNATURE) {
+            Some(CarrierFormat::Jpeg)
+        } else if data.starts_with(&GIF_SIGNATURE) {
+            Some(CarrierFormat::Gif)
+        } else if data.starts_with(&BMP_SIGNATURE) {
+            Some(CarrierFormat::Bmp)
+        } else if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+            Some(CarrierFormat::WebP)
+        } else {
+            None
+        }
+    }
+
+    /// 拡張子（ドット無し）
+    pub fn extension(self) -> &'static str {
+        match self {
+            CarrierFormat::Png => "png",
+            CarrierFormat::Jpeg => "jpg",
+            CarrierFormat::Gif => "gif",
+            CarrierFormat::Bmp => "bmp",
+            CarrierFormat::WebP => "webp",
+        }
+    }
+
+    /// 既定の設定のキャリア
+    pub fn carrier(self) -> &'static dyn Carrier {
+        match self {
+            CarrierFormat::Png => &PngCarrier {
+                layout: PnkLayout::Trailing,
+            },
+            CarrierFormat::Jpeg => &JpegCarrier {
+                quality: JPEG_DEFAULT_QUALITY,
+            },
+            CarrierFormat::Gif => &GifCarrier,
+            CarrierFormat::Bmp => &BmpCarrier,
+            CarrierFormat::WebP => &WebPCarrier,
+        }
+    }
+}
+
+/// カバー画像を運ぶ画像形式
+///
+/// `encode_image` と `find_end` を実装すれば、`[画像][フレーム]` の形で
+/// `embed` / `extract` が使える。
+pub trait Carrier {
+    fn format(&self) -> CarrierFormat;
+
+    /// RGBAのカバーをこの形式の画像にエンコード
+    fn encode_image(&self, rgba: &[u8], width: usize, height: usize) -> Result<Vec<u8>, PinkError>;
+
+    /// 画像部分の終端（フレームの開始位置）を探す
+    fn find_end(&self, data: &[u8]) -> Result<usize, PinkError>;
+
+    /// フレームのカバーを画像にして、その後ろにフレームを付ける
+    fn embed(&self, frame: &[u8]) -> Result<Vec<u8>, PinkError> {
+        let header = FrameHeader::parse(frame)?;
+        let mut out = self.encode_image(
+            &frame[header.cover_range()],
+            header.cover_width,
+            header.cover_height,
+        )?;
+        out.extend_from_slice(frame);
+        Ok(out)
+    }
+
+    /// 画像の後ろのPINK-072フレームを取り出す
+    ///
+    /// マジック `PINK` で始まらず、旧形式のフレームとしても
+    /// 認識できないデータは `InvalidFormat` になる。
+    fn extract<'a>(&self, data: &'a [u8]) -> Result<Cow<'a, [u8]>, PinkError> {
+        let frame = &data[self.find_end(data)?..];
+        if !frame.starts_with(&FRAME_MAGIC) && FrameHeader::sniff(frame).is_none() {
+            return Err(PinkError::InvalidFormat);
+        }
+        Ok(Cow::Borrowed(frame))
+    }
+}
+
+/// PNG（`encode_pnk_with_layout` / `decode_pnk` と同じ）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PngCarrier {
+    pub layout: PnkLayout,
+}
+
+impl Carrier for PngCarrier {
+    fn format(&self) -> CarrierFormat {
+        CarrierFormat::Png
+    }
+
+    fn encode_image(&self, rgba: &[u8], width: usize, height: usize) -> Result<Vec<u8>, PinkError> {
+        Ok(encode_png_rgba(rgba, width, height))
+    }
+
+    fn find_end(&self, data: &[u8]) -> Result<usize, PinkError> {
+        find_png_end(data)
+    }
+
+    fn embed(&self, frame: &[u8]) -> Result<Vec<u8>, PinkError> {
+        encode_pnk_with_layout(frame, self.layout)
+    }
+
+    fn extract<'a>(&self, data: &'a [u8]) -> Result<Cow<'a, [u8]>, PinkError> {
+        decode_pnk(data)
+    }
+}
+
+/// ベースラインJPEG（EOIの後ろにフレーム）
+///
+/// 非可逆なので、表示されるカバーはフレーム内のカバーと完全には一致しない。
+#[derive(Debug, Clone, Copy)]
+pub struct JpegCarrier {
+    /// 品質（1〜100）
+    pub quality: u8,
+}
+
+impl Default for JpegCarrier {
+    fn default() -> Self {
+        JpegCarrier {
+            quality: JPEG_DEFAULT_QUALITY,
+        }
+    }
+}
+
+impl Carrier for JpegCarrier {
+    fn format(&self) -> CarrierFormat {
+        CarrierFormat::Jpeg
+    }
+
+    fn encode_image(&self, rgba: &[u8], width: usize, height: usize) -> Result<Vec<u8>, PinkError> {
+        encode_jpeg_rgba(rgba, width, height, self.quality)
+    }
+
+    fn find_end(&self, data: &[u8]) -> Result<usize, PinkError> {
+        find_jpeg_end(data)
+    }
+}
+
+/// GIF（トレーラの後ろにフレーム）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GifCarrier;
+
+impl Carrier for GifCarrier {
+    fn format(&self) -> CarrierFormat {
+        CarrierFormat::Gif
+    }
+
+    fn encode_image(&self, rgba: &[u8], width: usize, height: usize) -> Result<Vec<u8>, PinkError> {
+        encode_gif_rgba(rgba, width, height)
+    }
+
+    fn find_end(&self, data: &[u8]) -> Result<usize, PinkError> {
+        find_gif_end(data)
+    }
+}
+
+/// BMP（ピクセル配列の後ろにフレーム）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct BmpCarrier;
+
+impl Carrier for BmpCarrier {
+    fn format(&self) -> CarrierFormat {
+        CarrierFormat::Bmp
+    }
+
+    fn encode_image(&self, rgba: &[u8], width: usize, height: usize) -> Result<Vec<u8>, PinkError> {
+        encode_bmp_rgba(rgba, width, height)
+    }
+
+    fn find_end(&self, data: &[u8]) -> Result<usize, PinkError> {
+        find_bmp_end(data)
+    }
+}
+
+/// ロスレスWebP（RIFFコンテナの後ろにフレーム）
+#[derive(Debug, Clone, Copy, Default)]
+pub struct WebPCarrier;
+
+impl Carrier for WebPCarrier {
+    fn format(&self) -> CarrierFormat {
+        CarrierFormat::WebP
+    }
+
+    fn encode_image(&self, rgba: &[u8], width: usize, height: usize) -> Result<Vec<u8>, PinkError> {
+        encode_webp_rgba(rgba, width, height)
+    }
+
+    fn find_end(&self, data: &[u8]) -> Result<usize, PinkError> {
+        find_webp_end(data)
+    }
+}
+
+/// 既定の設定のキャリアでPINK-072フレームを画像に埋め込む
+///
+/// `CarrierFormat::Png` は `encode_pnk` と同じ出力になる。
+pub fn encode_carrier(frame: &[u8], format: CarrierFormat) -> Result<Vec<u8>, PinkError> {
+    format.carrier().embed(frame)
+}
+
+/// 画像の形式を自動判定してPINK-072フレームを取り出す
+///
+/// 対応していない形式は `InvalidFormat` になる。
+pub fn decode_carrier(data: &[u8]) -> Result<(CarrierFormat, Cow<'_, [u8]>), PinkError> {
+    let format = CarrierFormat::detect(data).ok_or(PinkError::InvalidFormat)?;
+    let frame = format.carrier().extract(data)?;
+    Ok((format, frame))
+}
@@ -4,3 +4,19 @@ pub const COVER_HEIGHT: usize = 72;
 pub const COVER_PIXELS: usize = COVER_WIDTH * COVER_HEIGHT;
 pub const COVER_LEN: usize = COVER_PIXELS * 4;
 pub const BLOCK_SIZE: usize = 16;
+
+/// フレームヘッダ先頭のマジック
+pub const FRAME_MAGIC: [u8; 4] = *b"PINK";
+/// 現在のフレームヘッダのバージョン（マジックの無い旧形式は 1）
+pub const FRAME_VERSION: u8 = 2;
+
+/// ヘッダのフラグ: カバーは呼び出し元が用意した画像（シードから再生成できない）
+pub const FLAG_EXTERNAL_COVER: u8 = 0x01;
+/// ヘッダのフラグ: ペイロードの後ろに署名トレーラがある
+pub const FLAG_SIGNED: u8 = 0x02;
+
+/// ヘッダのフラグ: ペイロードは受信者の公開鍵向けに暗号化されている
+pub const FLAG_ENCRYPTED: u8 = 0x04;
+
+/// 署名トレーラの長さ（鍵ID 8バイト + Ed25519署名 64バイト）
+pub const SIGNATURE_TRAILER_LEN: usize = 8 + 64;
@@ -1,8 +1,21 @@
-use crate::constants::{BLOCK_SIZE, COVER_LEN, HEADER_LEN};
-use crate::cover::{generate_cover, validate_seed};
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+use core::ops::Range;
+
+use crate::constants::{
+    BLOCK_SIZE, COVER_HEIGHT, COVER_LEN, COVER_WIDTH, FLAG_ENCRYPTED, FLAG_EXTERNAL_COVER,
+    FLAG_SIGNED, FRAME_MAGIC, FRAME_VERSION, HEADER_LEN, SIGNATURE_TRAILER_LEN,
+};
+use crate::cover::generate_cover;
 use crate::error::PinkError;
+use crate::seed::Seed9;
 
-pub fn pink072_wrap(payload: &[u8], payload_type: u8, seed9: &[u8]) -> Result<Vec<u8>, PinkError> {
+#[cfg(feature = "alloc")]
+pub fn pink072_wrap(
+    payload: &[u8],
+    payload_type: u8,
+    seed9: impl Into<Seed9>,
+) -> Result<Vec<u8>, PinkError> {
     let total_len = HEADER_LEN + COVER_LEN + payload.len();
     let mut frame = vec![0u8; total_len];
     pink072_wrap_into(payload, payload_type, seed9, &mut frame)?;
@@ -12,26 +25,18 @@ pub fn pink072_wrap(payload: &[u8], payload_type: u8, seed9: &[u8]) -> Result<Ve
 pub fn pink072_wrap_into(
     payload: &[u8],
     payload_type: u8,
-    seed9: &[u8],
+    seed9: impl Into<Seed9>,
     out_frame: &mut [u8],
 ) -> Result<usize, PinkError> {
-    validate_seed(seed9)?;
     let total_len = HEADER_LEN + COVER_LEN + payload.len();
     if out_frame.len() < total_len {
         return Err(PinkError::BufferTooSmall);
     }
 
-    out_frame[0] = 1;
-    out_frame[1] = payload_type;
-    out_frame[2] = BLOCK_SIZE as u8;
-    out_frame[3] = 0; // reserved (was strength)
-
-    let payload_len = payload.len() as u64;
-    out_frame[4..12].copy_from_slice(&payload_len.to_le_bytes());
-    out_frame[12..HEADER_LEN].fill(0);
+    write_header(out_frame, payload_type, 0, payload.len());
 
     let cover_range = HEADER_LEN..HEADER_LEN + COVER_LEN;
-    generate_cover(&mut out_frame[cover_range.clone()], seed9);
+    generate_cover(&mut out_frame[cover_range.clone()], &seed9.into());
 
     let payload_range = cover_range.end..cover_range.end + payload.len();
     out_frame[payload_range].copy_from_slice(payload);
@@ -39,25 +44,304 @@ pub fn pink072_wrap_into(
     Ok(total_len)
 }
 
-pub fn pink072_unwrap(frame: &[u8]) -> Result<(u8, Vec<u8>), PinkError> {
-    if frame.len() < HEADER_LEN + COVER_LEN {
-        return Err(PinkError::FrameTooSmall);
+/// 任意のRGBA画像をカバーとしてフレームを生成する
+///
+/// シードから再生成できないため、ヘッダに `FLAG_EXTERNAL_COVER` を立て、
+/// 幅・高さを記録する。
+#[cfg(feature = "alloc")]
+pub fn pink072_wrap_with_cover(
+    payload: &[u8],
+    payload_type: u8,
+    rgba: &[u8],
+    width: u32,
+    height: u32,
+) -> Result<Vec<u8>, PinkError> {
+    let cover_len = (width as usize)
+        .checked_mul(height as usize)
+        .and_then(|pixels| pixels.checked_mul(4))
+        .ok_or(PinkError::InvalidCover)?;
+    if width == 0 || height == 0 || rgba.len() != cover_len {
+        return Err(PinkError::InvalidCover);
     }
 
-    let payload_type = frame[1];
-    let mut len_bytes = [0u8; 8];
-    len_bytes.copy_from_slice(&frame[4..12]);
-    let payload_len = usize::try_from(u64::from_le_bytes(len_bytes))
-        .map_err(|_| PinkError::PayloadLengthOverflow)?;
+    let total_len = HEADER_LEN + cover_len + payload.len();
+    let mut frame = vec![0u8; total_len];
+    write_header(&mut frame, payload_type, FLAG_EXTERNAL_COVER, payload.len());
+    frame[16..20].copy_from_slice(&width.to_le_bytes());
+    frame[20..24].copy_from_slice(&height.to_le_bytes());
+
+    frame[HEADER_LEN..HEADER_LEN + cover_len].copy_from_slice(rgba);
+    frame[HEADER_LEN + cover_len..].copy_from_slice(payload);
+    Ok(frame)
+}
+
+/// ヘッダ（v2）を書き込む
+///
+/// `[0..4]` マジック `PINK`、`[4]` バージョン、`[5]` ペイロードタイプ、
+/// `[6]` ブロックサイズ、`[7]` フラグ、`[8..16]` ペイロード長（LE）、
+/// `[16..20]` / `[20..24]` 外部カバーの幅・高さ（LE）、
+/// `[24..28]` ヘッダ拡張領域の長さ（LE）、`[28..32]` 予約
+///
+/// 拡張領域はヘッダとカバーの間に置く（長さは呼び出し側で書き込む）。
+pub(crate) fn write_header(out_frame: &mut [u8], payload_type: u8, flags: u8, payload_len: usize) {
+    out_frame[0..4].copy_from_slice(&FRAME_MAGIC);
+    out_frame[4] = FRAME_VERSION;
+    out_frame[5] = payload_type;
+    out_frame[6] = BLOCK_SIZE as u8;
+    out_frame[7] = flags;
 
-    let payload_start = HEADER_LEN + COVER_LEN;
-    let payload_end = payload_start
-        .checked_add(payload_len)
-        .ok_or(PinkError::PayloadLengthOverflow)?;
+    let payload_len = payload_len as u64;
+    out_frame[8..16].copy_from_slice(&payload_len.to_le_bytes());
+    out_frame[16..HEADER_LEN].fill(0);
+}
+
+/// フレームを展開する
+///
+/// 暗号化されたフレームは `Encrypted` になる（`pink072_unwrap_encrypted` を使う）。
+#[cfg(feature = "alloc")]
+pub fn pink072_unwrap(frame: &[u8]) -> Result<(u8, Vec<u8>), PinkError> {
+    let header = FrameHeader::parse(frame)?;
+    if header.is_encrypted() {
+        return Err(PinkError::Encrypted);
+    }
+    let payload_range = header.payload_range()?;
 
-    if frame.len() < payload_end {
+    if frame.len() < payload_range.end {
         return Err(PinkError::TruncatedFrame);
     }
 
-    Ok((payload_type, frame[payload_start..payload_end].to_vec()))
+    Ok((header.payload_type, frame[payload_range].to_vec()))
+}
+
+/// シードを検証しながらフレームを展開する（厳格モード）
+///
+/// フレーム内のカバーを `seed9` から再生成した画像と比較し、
+/// 一致しなければ `CoverMismatch` を返す。外部カバーのフレームは常に拒否する。
+#[cfg(feature = "alloc")]
+pub fn pink072_unwrap_strict(
+    frame: &[u8],
+    seed9: impl Into<Seed9>,
+) -> Result<(u8, Vec<u8>), PinkError> {
+    let unwrapped = pink072_unwrap(frame)?;
+    let header = FrameHeader::parse(frame)?;
+    if header.is_external_cover() {
+        return Err(PinkError::CoverMismatch);
+    }
+    verify_cover(&frame[header.cover_range()], &seed9.into())?;
+    Ok(unwrapped)
+}
+
+/// カバーがシードから生成された画像と一致するか確認
+#[cfg(feature = "alloc")]
+pub(crate) fn verify_cover(cover: &[u8], seed9: &Seed9) -> Result<(), PinkError> {
+    let mut expected = vec![0u8; COVER_LEN];
+    generate_cover(&mut expected, seed9);
+    if cover == expected.as_slice() {
+        Ok(())
+    } else {
+        Err(PinkError::CoverMismatch)
+    }
+}
+
+/// フレームヘッダの情報
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameInfo {
+    /// ヘッダのバージョン（1 はマジックの無い旧形式）
+    pub version: u8,
+    pub payload_type: u8,
+    pub flags: u8,
+    pub cover_width: u32,
+    pub cover_height: u32,
+    pub payload_len: u64,
+}
+
+/// 既知のフラグ（これ以外のビットが立っていればフレームらしくない）
+const KNOWN_FLAGS: u8 = FLAG_EXTERNAL_COVER | FLAG_SIGNED | FLAG_ENCRYPTED;
+
+/// フレームヘッダの解析結果
+pub(crate) struct FrameHeader {
+    pub version: u8,
+    pub payload_type: u8,
+    pub flags: u8,
+    pub cover_width: usize,
+    pub cover_height: usize,
+    pub payload_len: u64,
+    /// ヘッダ拡張領域の長さ（旧形式は常に0）
+    pub extension_len: usize,
+}
+
+impl FrameHeader {
+    /// ヘッダ（先頭 `HEADER_LEN` バイト）だけを解析する
+    ///
+    /// マジック `PINK` で始まればv2、先頭が `1` なら旧形式（v1）として読む。
+    pub fn read(header: &[u8]) -> Result<Self, PinkError> {
+        if header.len() < HEADER_LEN {
+            return Err(PinkError::FrameTooSmall);
+        }
+
+        // (バージョン, タイプ, フラグ, ペイロード長の位置, カバー寸法の位置, 拡張領域の長さ)
+        let (version, payload_type, flags, len_at, dims_at, extension_len) =
+            if header[0..4] == FRAME_MAGIC {
+                if header[4] != FRAME_VERSION {
+                    return Err(PinkError::UnsupportedVersion { version: header[4] });
+                }
+                let extension_len = read_u32_le(&header[24..]) as usize;
+                (header[4], header[5], header[7], 8, 16, extension_len)
+            } else if header[0] == 1 {
+                (1, header[1], header[3], 4, 12, 0)
+            } else {
+                return Err(PinkError::UnsupportedVersion { version: header[0] });
+            };
+
+        let (cover_width, cover_height) = if flags & FLAG_EXTERNAL_COVER != 0 {
+            let width = read_u32_le(&header[dims_at..]);
+            let height = read_u32_le(&header[dims_at + 4..]);
+            if width == 0 || height == 0 {
+                return Err(PinkError::InvalidCover);
+            }
+            (width as usize, height as usize)
+        } else {
+            (COVER_WIDTH, COVER_HEIGHT)
+        };
+
+        let mut len_bytes = [0u8; 8];
+        len_bytes.copy_from_slice(&header[len_at..len_at + 8]);
+
+        Ok(FrameHeader {
+            version,
+            payload_type,
+            flags,
+            cover_width,
+            cover_height,
+            payload_len: u64::from_le_bytes(len_bytes),
+            extension_len,
+        })
+    }
+
+    /// ヘッダを解析し、カバー部分までがフレームに含まれることを確認する
+    pub fn parse(frame: &[u8]) -> Result<Self, PinkError> {
+        let header = FrameHeader::read(frame)?;
+        let cover_end = header
+            .cover_len()
+            .and_then(|len| len.checked_add(HEADER_LEN))
+            .and_then(|len| len.checked_add(header.extension_len))
+            .ok_or(PinkError::InvalidCover)?;
+        if frame.len() < cover_end {
+            return Err(PinkError::FrameTooSmall);
+        }
+        Ok(header)
+    }
+
+    /// ヘッダがPINK-072フレームらしい場合のみ解析する（検出・走査用）
+    ///
+    /// v2はマジックで判定する。マジックの無い旧形式は、ブロックサイズ・
+    /// 未使用フラグ・予約領域がすべて既定値の場合のみ受け付ける。
+    pub fn sniff(header: &[u8]) -> Option<Self> {
+        if header.len() < HEADER_LEN {
+            return None;
+        }
+        let (block_size, flags, dims, reserved) = if header[0..4] == FRAME_MAGIC {
+            (
+                header[6],
+                header[7],
+                &header[16..24],
+                &header[28..HEADER_LEN],
+            )
+        } else if header[0] == 1 {
+            (
+                header[2],
+                header[3],
+                &header[12..20],
+                &header[20..HEADER_LEN],
+            )
+        } else {
+            return None;
+        };
+
+        if block_size != BLOCK_SIZE as u8
+            || flags & !KNOWN_FLAGS != 0
+            || reserved.iter().any(|&b| b != 0)
+            || (flags & FLAG_EXTERNAL_COVER == 0 && dims.iter().any(|&b| b != 0))
+        {
+            return None;
+        }
+        FrameHeader::read(header).ok()
+    }
+
+    pub fn info(&self) -> FrameInfo {
+        FrameInfo {
+            version: self.version,
+            payload_type: self.payload_type,
+            flags: self.flags,
+            cover_width: self.cover_width as u32,
+            cover_height: self.cover_height as u32,
+            payload_len: self.payload_len,
+        }
+    }
+
+    pub fn is_external_cover(&self) -> bool {
+        self.flags & FLAG_EXTERNAL_COVER != 0
+    }
+
+    pub fn is_signed(&self) -> bool {
+        self.flags & FLAG_SIGNED != 0
+    }
+
+    pub fn is_encrypted(&self) -> bool {
+        self.flags & FLAG_ENCRYPTED != 0
+    }
+
+    /// ヘッダ拡張領域の範囲（ヘッダとカバーの間）
+    pub fn extension_range(&self) -> Range<usize> {
+        HEADER_LEN..HEADER_LEN + self.extension_len
+    }
+
+    fn cover_len(&self) -> Option<usize> {
+        self.cover_width
+            .checked_mul(self.cover_height)
+            .and_then(|pixels| pixels.checked_mul(4))
+    }
+
+    /// カバーの範囲（`parse` 済みなのでオーバーフローしない）
+    pub fn cover_range(&self) -> Range<usize> {
+        let start = self.extension_range().end;
+        start..start + self.cover_width * self.cover_height * 4
+    }
+
+    /// フレーム全体の長さ（ヘッダから、署名トレーラがあればその末尾まで）
+    pub fn frame_len(&self) -> Result<usize, PinkError> {
+        let trailer_len = if self.is_signed() {
+            SIGNATURE_TRAILER_LEN
+        } else {
+            0
+        };
+        self.payload_range()?
+            .end
+            .checked_add(trailer_len)
+            .ok_or(PinkError::PayloadLengthOverflow)
+    }
+
+    /// ペイロードの範囲
+    pub fn payload_range(&self) -> Result<Range<usize>, PinkError> {
+        let payload_len =
+            usize::try_from(self.payload_len).map_err(|_| PinkError::PayloadLengthOverflow)?;
+        let payload_start = self.cover_range().end;
+        let payload_end = payload_start
+            .checked_add(payload_len)
+            .ok_or(PinkError::PayloadLengthOverflow)?;
+        Ok(payload_start..payload_end)
+    }
+}
+
+/// `candidate` の先頭がPINK-072フレームらしければ、そのフレーム長を返す
+///
+/// `FrameHeader::sniff` を通り、ペイロードが `candidate` に収まる場合のみ一致とみなす。
+pub(crate) fn plausible_frame_len(candidate: &[u8]) -> Option<usize> {
+    let header = FrameHeader::sniff(candidate)?;
+    let frame_len = header.frame_len().ok()?;
+    (frame_len <= candidate.len()).then_some(frame_len)
+}
+
+fn read_u32_le(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
 }
@@ -1,39 +1,54 @@
+#[cfg(feature = "alloc")]
+use alloc::{vec, vec::Vec};
+
+#[cfg(feature = "alloc")]
+use crate::constants::COVER_LEN;
 use crate::constants::{COVER_HEIGHT, COVER_WIDTH};
+#[cfg(feature = "alloc")]
 use crate::error::PinkError;
-use crate::noise::{generate_permutation, perlin2d, seed9_to_u64, xorshift64};
+use crate::noise::{generate_permutation, perlin2d, seed9_to_u64, xorshift64, FIXED_ONE};
+#[cfg(feature = "alloc")]
+use crate::png::{encode_apng_rgba, encode_png_rgba};
+use crate::seed::Seed9;
 
-pub fn generate_cover(buf: &mut [u8], seed9: &[u8]) {
-    let mut state = seed9_to_u64(seed9);
-    let perm = generate_permutation(&mut state);
+/// シードからカバー画像を生成する
+///
+/// 整数演算のみで計算するため、どの環境でも同じシードから同じ画像になる。
+pub fn generate_cover(buf: &mut [u8], seed9: &Seed9) {
+    generate_cover_shifted(buf, seed9, 0, 0);
+}
 
-    // 大きめの模様が2〜3個見える程度のスケール
-    let base_scale = 0.04;
+/// ノイズの参照位置を `shift_x`, `shift_y`（Q16.16のピクセル単位）だけずらしてカバーを生成
+fn generate_cover_shifted(buf: &mut [u8], seed9: &Seed9, shift_x: i64, shift_y: i64) {
+    let mut state = seed9_to_u64(seed9.as_bytes());
+    let perm = generate_permutation(&mut state);
 
     // オフセット（シードごとに異なる位置から開始）
-    let offset_x = (xorshift64(&mut state) % 1000) as f32;
-    let offset_y = (xorshift64(&mut state) % 1000) as f32;
+    let offset_x = (xorshift64(&mut state) % 1000) as i64;
+    let offset_y = (xorshift64(&mut state) % 1000) as i64;
 
     for y in 0..COVER_HEIGHT {
         for x in 0..COVER_WIDTH {
             let pixel = y * COVER_WIDTH + x;
             let base = pixel * 4;
 
-            let fx = x as f32 + offset_x;
-            let fy = y as f32 + offset_y;
+            let fx = (x as i64 + offset_x) * FIXED_ONE + shift_x;
+            let fy = (y as i64 + offset_y) * FIXED_ONE + shift_y;
 
             // 2層のノイズで滑らかな丸い光のような模様
-            let n1 = perlin2d(fx * base_scale, fy * base_scale, &perm);
-            let n2 = perlin2d(fx * base_scale * 0.6, fy * base_scale * 0.6, &perm);
-            let noise = n1 * 0.5 + n2 * 0.5;
+            // 大きめの模様が2〜3個見える程度のスケール（0.04 と 0.024）
+            let n1 = perlin2d(fx / 25, fy / 25, &perm);
+            let n2 = perlin2d(fx * 3 / 125, fy * 3 / 125, &perm);
+            let noise = (n1 + n2) >> 1;
 
             // 滑らかな補間でピンク〜白のグラデーション
-            let t = noise.clamp(0.0, 1.0);
-            let t_smooth = t * t * (3.0 - 2.0 * t); // smoothstep
+            let t = noise.clamp(0, FIXED_ONE);
+            let t_smooth = (((t * t) >> 16) * (3 * FIXED_ONE - 2 * t)) >> 16; // smoothstep
 
             // ピンク (255, 170, 185) → 薄ピンク (255, 235, 240)
             let r = 255;
-            let g = (170.0 + (235.0 - 170.0) * t_smooth) as u8;
-            let b = (185.0 + (240.0 - 185.0) * t_smooth) as u8;
+            let g = (170 + (((235 - 170) * t_smooth) >> 16)) as u8;
+            let b = (185 + (((240 - 185) * t_smooth) >> 16)) as u8;
 
             buf[base] = r;
             buf[base + 1] = g;
@@ -43,10 +58,110 @@ pub fn generate_cover(buf: &mut [u8], seed9: &[u8]) {
     }
 }
 
-pub fn validate_seed(seed9: &[u8]) -> Result<(), PinkError> {
-    if seed9.len() != 9 {
-        Err(PinkError::SeedLength)
-    } else {
-        Ok(())
+/// アニメーション1フレームあたりのノイズの移動量（Q16.16、1/4ピクセル）
+#[cfg(feature = "alloc")]
+const ANIMATION_STEP: i64 = FIXED_ONE / 4;
+
+/// `render_cover_png` の描画スタイル
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CoverStyle {
+    /// そのままの正方形
+    #[default]
+    Square,
+    /// 円形に切り抜き（外側は透明）、アイコン向け
+    Circle,
+}
+
+/// `render_cover_png` で描画できる最大の一辺（4096x4096のRGBAで64MiB）
+#[cfg(feature = "alloc")]
+pub const COVER_RENDER_MAX_SIZE: u32 = 4096;
+
+/// `render_cover_apng` で描画できる最大のフレーム数（72x72のRGBAで約21MiB）
+#[cfg(feature = "alloc")]
+pub const COVER_APNG_MAX_FRAMES: u32 = 1024;
+
+/// シードのカバーだけを任意サイズのPNGとして描画する
+///
+/// 72x72のカバーを最近傍補間で `size` x `size` に拡大（縮小）する。
+/// 同じシードなら常に同じ画像になるので、アイデンティコンとして使える。
+/// `size` が0か `COVER_RENDER_MAX_SIZE` を超えれば `InvalidCover`。
+#[cfg(feature = "alloc")]
+pub fn render_cover_png(
+    seed9: impl Into<Seed9>,
+    size: u32,
+    style: CoverStyle,
+) -> Result<Vec<u8>, PinkError> {
+    if size == 0 || size > COVER_RENDER_MAX_SIZE {
+        return Err(PinkError::InvalidCover);
+    }
+    let size = size as usize;
+    let rgba_len = size
+        .checked_mul(size)
+        .and_then(|pixels| pixels.checked_mul(4))
+        .ok_or(PinkError::InvalidCover)?;
+
+    let mut cover = vec![0u8; COVER_LEN];
+    generate_cover(&mut cover, &seed9.into());
+
+    let mut rgba = vec![0u8; rgba_len];
+    for y in 0..size {
+        let src_y = y * COVER_HEIGHT / size;
+        for x in 0..size {
+            let src_x = x * COVER_WIDTH / size;
+            let src = (src_y * COVER_WIDTH + src_x) * 4;
+            let dst = (y * size + x) * 4;
+            rgba[dst..dst + 4].copy_from_slice(&cover[src..src + 4]);
+
+            if style == CoverStyle::Circle {
+                // ピクセル中心と画像中心の距離（2倍スケールの整数で比較）
+                let dx = (2 * x + 1) as i64 - size as i64;
+                let dy = (2 * y + 1) as i64 - size as i64;
+                if dx * dx + dy * dy > (size * size) as i64 {
+                    rgba[dst + 3] = 0;
+                }
+            }
+        }
+    }
+
+    Ok(encode_png_rgba(&rgba, size, size))
+}
+
+/// シードのカバーをゆっくり動くAPNGとして描画する
+///
+/// ノイズの参照位置を `frames` フレームかけて往復させるため、継ぎ目なくループする。
+/// 1フレーム目は静止画のカバーと同一。
+/// `frames` が0か `COVER_APNG_MAX_FRAMES` を超えれば `InvalidCover`。
+#[cfg(feature = "alloc")]
+pub fn render_cover_apng(
+    seed9: impl Into<Seed9>,
+    frames: u32,
+    delay_ms: u16,
+) -> Result<Vec<u8>, PinkError> {
+    if frames == 0 || frames > COVER_APNG_MAX_FRAMES {
+        return Err(PinkError::InvalidCover);
     }
+
+    let seed9 = seed9.into();
+    let images: Vec<Vec<u8>> = (0..frames)
+        .map(|i| {
+            // 三角波（0 → frames/2 → 0）で往復
+            let phase = i.min(frames - i) as i64;
+            let mut cover = vec![0u8; COVER_LEN];
+            generate_cover_shifted(
+                &mut cover,
+                &seed9,
+                phase * ANIMATION_STEP,
+                phase * ANIMATION_STEP / 2,
+            );
+            cover
+        })
+        .collect();
+
+    Ok(encode_apng_rgba(
+        &images,
+        COVER_WIDTH,
+        COVER_HEIGHT,
+        delay_ms,
+    ))
 }
@@ -0,0 +1,249 @@
+//! X25519による受信者向けの暗号化
+//!
+//! ペイロードはランダムなファイル鍵でChaCha20-Poly1305により一度だけ暗号化し、
+//! ファイル鍵を受信者の公開鍵ごとに包んでヘッダ拡張領域に格納する。
+//!
+//! 拡張領域はレコード `[タグ 1B][長さ 4B (LE)][本体]` の並び。受信者レコードの本体は
+//! `[受信者数 2B (LE)]` に続けて、受信者ごとに
+//! `[鍵ID 8B][一時公開鍵 32B][包んだファイル鍵 48B]`。
+
+use alloc::vec::Vec;
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use hkdf::Hkdf;
+use sha2::Sha256;
+use x25519_dalek::{PublicKey, SharedSecret, StaticSecret};
+
+use crate::constants::FLAG_SIGNED;
+use crate::core::FrameHeader;
+use crate::error::PinkError;
+use crate::sign::KeyId;
+
+// 暗号化（鍵の生成に乱数源が要る）
+#[cfg(feature = "rand")]
+use crate::constants::{COVER_LEN, FLAG_ENCRYPTED, HEADER_LEN};
+#[cfg(feature = "rand")]
+use crate::core::write_header;
+#[cfg(feature = "rand")]
+use crate::cover::generate_cover;
+#[cfg(feature = "rand")]
+use crate::seed::Seed9;
+#[cfg(feature = "rand")]
+use alloc::vec;
+
+/// 拡張領域のレコード: 受信者一覧
+const EXT_RECIPIENTS: u8 = 0x01;
+
+/// 受信者1人分の長さ（鍵ID + 一時公開鍵 + 包んだファイル鍵）
+const STANZA_LEN: usize = 8 + 32 + 32 + TAG_LEN;
+
+/// Poly1305タグの長さ
+const TAG_LEN: usize = 16;
+
+/// ファイル鍵を包む鍵の導出に使うHKDFのinfo
+const WRAP_INFO: &[u8] = b"PINK-072 x25519 v1";
+
+/// 鍵は毎回ランダムに作るため、ノンスは固定でよい
+const NONCE: [u8; 12] = [0; 12];
+
+/// 受信者の公開鍵でペイロードを暗号化してフレームを生成する
+///
+/// 受信者は1人以上（空なら `NoRecipients`）。ヘッダには `FLAG_ENCRYPTED` を立て、
+/// ペイロード長は暗号文（平文 + 16バイトのタグ）の長さになる。カバーは暗号化しない。
+#[cfg(feature = "rand")]
+pub fn pink072_wrap_encrypted(
+    payload: &[u8],
+    payload_type: u8,
+    seed9: impl Into<Seed9>,
+    recipients: &[PublicKey],
+) -> Result<Vec<u8>, PinkError> {
+    if recipients.is_empty() {
+        return Err(PinkError::NoRecipients);
+    }
+    if recipients.len() > u16::MAX as usize {
+        return Err(PinkError::PayloadLengthOverflow);
+    }
+
+    let file_key = random_bytes()?;
+    let mut record = Vec::with_capacity(2 + recipients.len() * STANZA_LEN);
+    record.extend_from_slice(&(recipients.len() as u16).to_le_bytes());
+    for recipient in recipients {
+        let ephemeral = StaticSecret::from(random_bytes()?);
+        let ephemeral_public = PublicKey::from(&ephemeral);
+        let shared = ephemeral.diffie_hellman(recipient);
+        let wrap_key = derive_wrap_key(&shared, &ephemeral_public, recipient)?;
+        let wrapped = ChaCha20Poly1305::new(Key::from_slice(&wrap_key))
+            .encrypt(Nonce::from_slice(&NONCE), file_key.as_slice())
+            .map_err(|_| PinkError::EncryptionFailed)?;
+
+        record.extend_from_slice(&KeyId::of_recipient(recipient).0);
+        record.extend_from_slice(ephemeral_public.as_bytes());
+        record.extend_from_slice(&wrapped);
+    }
+
+    let mut extension = vec![EXT_RECIPIENTS];
+    extension.extend_from_slice(&(record.len() as u32).to_le_bytes());
+    extension.extend_from_slice(&record);
+
+    let payload_len = payload.len() + TAG_LEN;
+    let mut frame = vec![0u8; HEADER_LEN + extension.len() + COVER_LEN];
+    write_header(&mut frame, payload_type, FLAG_ENCRYPTED, payload_len);
+    frame[24..28].copy_from_slice(&(extension.len() as u32).to_le_bytes());
+    frame[HEADER_LEN..HEADER_LEN + extension.len()].copy_from_slice(&extension);
+    generate_cover(&mut frame[HEADER_LEN + extension.len()..], &seed9.into());
+
+    let aad = associated_data(&frame[..HEADER_LEN + extension.len()]);
+    let ciphertext = ChaCha20Poly1305::new(Key::from_slice(&file_key))
+        .encrypt(
+            Nonce::from_slice(&NONCE),
+            Payload {
+                msg: payload,
+                aad: &aad,
+            },
+        )
+        .map_err(|_| PinkError::EncryptionFailed)?;
+    frame.extend_from_slice(&ciphertext);
+    Ok(frame)
+}
+
+/// 秘密鍵でフレームを復号して展開する
+///
+/// 暗号化されていないフレームはそのまま展開する。
+/// 秘密鍵に対応する受信者が無ければ `NotRecipient`、
+/// 暗号文や拡張領域が改変されていれば `DecryptionFailed` になる。
+pub fn pink072_unwrap_encrypted(
+    frame: &[u8],
+    secret_key: &StaticSecret,
+) -> Result<(u8, Vec<u8>), PinkError> {
+    let header = FrameHeader::parse(frame)?;
+    if !header.is_encrypted() {
+        return crate::core::pink072_unwrap(frame);
+    }
+    let payload_range = header.payload_range()?;
+    if frame.len() < payload_range.end {
+        return Err(PinkError::TruncatedFrame);
+    }
+
+    let public_key = PublicKey::from(secret_key);
+    let key_id = KeyId::of_recipient(&public_key);
+    let file_key = read_stanzas(&frame[header.extension_range()])?
+        .filter(|stanza| stanza.key_id == key_id)
+        .find_map(|stanza| {
+            let ephemeral_public = PublicKey::from(stanza.ephemeral);
+            let shared = secret_key.diffie_hellman(&ephemeral_public);
+            let wrap_key = derive_wrap_key(&shared, &ephemeral_public, &public_key).ok()?;
+            ChaCha20Poly1305::new(Key::from_slice(&wrap_key))
+                .decrypt(Nonce::from_slice(&NONCE), stanza.wrapped)
+                .ok()
+        })
+        .ok_or(PinkError::NotRecipient)?;
+    if file_key.len() != 32 {
+        return Err(PinkError::DecryptionFailed);
+    }
+
+    let aad = associated_data(&frame[..header.extension_range().end]);
+    let payload = ChaCha20Poly1305::new(Key::from_slice(&file_key))
+        .decrypt(
+            Nonce::from_slice(&NONCE),
+            Payload {
+                msg: &frame[payload_range],
+                aad: &aad,
+            },
+        )
+        .map_err(|_| PinkError::DecryptionFailed)?;
+    Ok((header.payload_type, payload))
+}
+
+/// 暗号化されたフレームの受信者の鍵IDを返す（暗号化されていなければ空）
+pub fn frame_recipients(frame: &[u8]) -> Result<Vec<KeyId>, PinkError> {
+    let header = FrameHeader::parse(frame)?;
+    if !header.is_encrypted() {
+        return Ok(Vec::new());
+    }
+    Ok(read_stanzas(&frame[header.extension_range()])?
+        .map(|stanza| stanza.key_id)
+        .collect())
+}
+
+/// 受信者1人分の情報
+struct Stanza<'a> {
+    key_id: KeyId,
+    ephemeral: [u8; 32],
+    wrapped: &'a [u8],
+}
+
+/// 拡張領域から受信者レコードを探し、受信者を順に返す
+fn read_stanzas(extension: &[u8]) -> Result<impl Iterator<Item = Stanza<'_>>, PinkError> {
+    let mut rest = extension;
+    while rest.len() >= 5 {
+        let tag = rest[0];
+        let len = u32::from_le_bytes([rest[1], rest[2], rest[3], rest[4]]) as usize;
+        let end = len.checked_add(5).ok_or(PinkError::InvalidFormat)?;
+        let body = rest.get(5..end).ok_or(PinkError::InvalidFormat)?;
+        rest = &rest[end..];
+        if tag != EXT_RECIPIENTS {
+            // 未知のレコードは読み飛ばす
+            continue;
+        }
+
+        let count = u16::from_le_bytes([
+            *body.first().ok_or(PinkError::InvalidFormat)?,
+            *body.get(1).ok_or(PinkError::InvalidFormat)?,
+        ]) as usize;
+        let stanzas = body
+            .get(2..2 + count * STANZA_LEN)
+            .ok_or(PinkError::InvalidFormat)?;
+        return Ok(stanzas.chunks_exact(STANZA_LEN).map(|stanza| {
+            let mut key_id = [0u8; 8];
+            key_id.copy_from_slice(&stanza[..8]);
+            let mut ephemeral = [0u8; 32];
+            ephemeral.copy_from_slice(&stanza[8..40]);
+            Stanza {
+                key_id: KeyId(key_id),
+                ephemeral,
+                wrapped: &stanza[40..],
+            }
+        }));
+    }
+    Err(PinkError::InvalidFormat)
+}
+
+/// 共有鍵からファイル鍵を包む鍵を導出する
+///
+/// ソルトは一時公開鍵と受信者の公開鍵。相手の公開鍵が低位の点で
+/// 共有鍵が0になる場合は `InvalidRecipientKey` で拒否する。
+fn derive_wrap_key(
+    shared: &SharedSecret,
+    ephemeral_public: &PublicKey,
+    recipient: &PublicKey,
+) -> Result<[u8; 32], PinkError> {
+    if !shared.was_contributory() {
+        return Err(PinkError::InvalidRecipientKey);
+    }
+
+    let mut salt = [0u8; 64];
+    salt[..32].copy_from_slice(ephemeral_public.as_bytes());
+    salt[32..].copy_from_slice(recipient.as_bytes());
+    let mut wrap_key = [0u8; 32];
+    Hkdf::<Sha256>::new(Some(&salt), shared.as_bytes())
+        .expand(WRAP_INFO, &mut wrap_key)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    Ok(wrap_key)
+}
+
+/// 追加認証データ: ヘッダ（署名フラグを除く）と拡張領域
+///
+/// 暗号化の後で署名してもAADが変わらないよう、`FLAG_SIGNED` だけは含めない。
+fn associated_data(header_and_extension: &[u8]) -> Vec<u8> {
+    let mut aad = header_and_extension.to_vec();
+    aad[7] &= !FLAG_SIGNED;
+    aad
+}
+
+#[cfg(feature = "rand")]
+fn random_bytes() -> Result<[u8; 32], PinkError> {
+    let mut bytes = [0u8; 32];
+    getrandom::getrandom(&mut bytes).map_err(|_| PinkError::Entropy)?;
+    Ok(bytes)
+}
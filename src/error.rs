@@ -1,4 +1,6 @@
-use std::fmt;
+use core::fmt;
+
+use crate::sign::KeyId;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum PinkError {
@@ -8,6 +10,87 @@ pub enum PinkError {
     PayloadLengthOverflow,
     TruncatedFrame,
     InvalidFormat,
+    CoverMismatch,
+    InvalidCover,
+    InvalidSeed,
+    Entropy,
+    /// PNGチャンクのCRCが一致しない（`offset` はチャンク先頭）
+    PngCrcMismatch {
+        offset: usize,
+    },
+    /// 最初のチャンクがIHDRではない
+    PngMissingIhdr,
+    /// チャンク長が上限を超える、またはデータ末尾を越える（`offset` はチャンク先頭）
+    PngChunkOverflow {
+        offset: usize,
+    },
+    /// IENDに到達する前にデータが終わった
+    PngMissingIend,
+    UnsupportedPng,
+    CorruptImageData,
+    /// 未対応のフレームヘッダのバージョン
+    UnsupportedVersion {
+        version: u8,
+    },
+    /// 画像の終端（EOI・トレーラ等）に到達する前にデータが終わった
+    MissingImageEnd,
+    /// 署名が求められたが、フレームに署名が無い
+    Unsigned,
+    /// 署名した鍵が鍵束に無い
+    UntrustedKey {
+        key_id: KeyId,
+    },
+    /// 署名が一致しない（フレームが改変されている）
+    BadSignature,
+    /// 暗号化されたフレームを秘密鍵無しで展開しようとした
+    Encrypted,
+    /// 暗号化の受信者が指定されていない
+    NoRecipients,
+    /// 受信者の公開鍵が不正（共有鍵が0になる低位の点）
+    InvalidRecipientKey,
+    /// 秘密鍵に対応する受信者がフレームに無い
+    NotRecipient,
+    /// 復号に失敗した（暗号文が改変されている）
+    DecryptionFailed,
+    /// 暗号化に失敗した（平文がAEADの上限を超える）
+    EncryptionFailed,
+}
+
+impl PinkError {
+    /// エラーの種類を表す安定した識別子（例: `"TRUNCATED_FRAME"`）
+    ///
+    /// メッセージと違って変わらないので、プログラムからの判定に使う。
+    pub fn code(&self) -> &'static str {
+        match self {
+            PinkError::SeedLength => "SEED_LENGTH",
+            PinkError::BufferTooSmall => "BUFFER_TOO_SMALL",
+            PinkError::FrameTooSmall => "FRAME_TOO_SMALL",
+            PinkError::PayloadLengthOverflow => "PAYLOAD_LENGTH_OVERFLOW",
+            PinkError::TruncatedFrame => "TRUNCATED_FRAME",
+            PinkError::InvalidFormat => "INVALID_FORMAT",
+            PinkError::CoverMismatch => "COVER_MISMATCH",
+            PinkError::InvalidCover => "INVALID_COVER",
+            PinkError::InvalidSeed => "INVALID_SEED",
+            PinkError::Entropy => "ENTROPY",
+            PinkError::PngCrcMismatch { .. } => "PNG_CRC_MISMATCH",
+            PinkError::PngMissingIhdr => "PNG_MISSING_IHDR",
+            PinkError::PngChunkOverflow { .. } => "PNG_CHUNK_OVERFLOW",
+            PinkError::PngMissingIend => "PNG_MISSING_IEND",
+            PinkError::UnsupportedPng => "UNSUPPORTED_PNG",
+            PinkError::CorruptImageData => "CORRUPT_IMAGE_DATA",
+            PinkError::UnsupportedVersion { .. } => "UNSUPPORTED_VERSION",
+            PinkError::MissingImageEnd => "MISSING_IMAGE_END",
+            PinkError::Unsigned => "UNSIGNED",
+            PinkError::UntrustedKey { .. } => "UNTRUSTED_KEY",
+            PinkError::BadSignature => "BAD_SIGNATURE",
+            PinkError::Encrypted => "ENCRYPTED",
+            PinkError::NoRecipients => "NO_RECIPIENTS",
+            PinkError::InvalidRecipientKey => "INVALID_RECIPIENT_KEY",
+            PinkError::NotRecipient => "NOT_RECIPIENT",
+            PinkError::DecryptionFailed => "DECRYPTION_FAILED",
+            PinkError::EncryptionFailed => "ENCRYPTION_FAILED",
+        }
+    }
 }
 
 impl fmt::Display for PinkError {
@@ -19,9 +102,39 @@ impl fmt::Display for PinkError {
             PinkError::PayloadLengthOverflow => "payload length overflow",
             PinkError::TruncatedFrame => "truncated frame",
             PinkError::InvalidFormat => "invalid PNK format",
+            PinkError::CoverMismatch => "cover does not match the expected seed",
+            PinkError::InvalidCover => "invalid cover dimensions",
+            PinkError::InvalidSeed => "invalid seed string",
+            PinkError::Entropy => "failed to obtain OS entropy",
+            PinkError::PngCrcMismatch { offset } => {
+                return write!(f, "bad CRC in PNG chunk at offset {offset}");
+            }
+            PinkError::PngMissingIhdr => "PNG does not start with an IHDR chunk",
+            PinkError::PngChunkOverflow { offset } => {
+                return write!(f, "PNG chunk length overflow at offset {offset}");
+            }
+            PinkError::PngMissingIend => "PNG ends without an IEND chunk",
+            PinkError::UnsupportedPng => "unsupported PNG (only 8-bit RGB/RGBA, non-interlaced)",
+            PinkError::CorruptImageData => "corrupt PNG image data",
+            PinkError::UnsupportedVersion { version } => {
+                return write!(f, "unsupported frame version {version}");
+            }
+            PinkError::MissingImageEnd => "image data ends before its end marker",
+            PinkError::Unsigned => "frame is not signed",
+            PinkError::UntrustedKey { key_id } => {
+                return write!(f, "frame is signed by an untrusted key {key_id}");
+            }
+            PinkError::BadSignature => "frame signature does not verify",
+            PinkError::Encrypted => "frame is encrypted; a secret key is required",
+            PinkError::NoRecipients => "at least one recipient is required",
+            PinkError::InvalidRecipientKey => "invalid recipient public key",
+            PinkError::NotRecipient => "frame is not encrypted for this key",
+            PinkError::DecryptionFailed => "failed to decrypt the payload",
+            PinkError::EncryptionFailed => "failed to encrypt the payload",
         };
         f.write_str(msg)
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for PinkError {}
@@ -0,0 +1,332 @@
+//! C ABI（`ffi` フィーチャー）
+//!
+//! 関数は成功で `PINK072_OK`（0）、失敗でエラーコードを返す。
+//! 出力のバッファはRustが確保し、呼び出し側が `pink072_buffer_free` で解放する。
+//! 宣言は `include/pink072.h`（`cbindgen --config cbindgen.toml` で再生成できる）。
+
+use std::ffi::{c_char, CStr};
+use std::panic::{catch_unwind, UnwindSafe};
+use std::path::Path;
+use std::ptr;
+
+use crate::error::PinkError;
+use crate::file::{decode_auto, encode_auto};
+use crate::png::{decode_pnk, encode_pnk};
+use crate::seed::Seed9;
+
+pub const PINK072_OK: i32 = 0;
+pub const PINK072_ERR_SEED_LENGTH: i32 = 1;
+pub const PINK072_ERR_BUFFER_TOO_SMALL: i32 = 2;
+pub const PINK072_ERR_FRAME_TOO_SMALL: i32 = 3;
+pub const PINK072_ERR_PAYLOAD_LENGTH_OVERFLOW: i32 = 4;
+pub const PINK072_ERR_TRUNCATED_FRAME: i32 = 5;
+pub const PINK072_ERR_INVALID_FORMAT: i32 = 6;
+pub const PINK072_ERR_COVER_MISMATCH: i32 = 7;
+pub const PINK072_ERR_INVALID_COVER: i32 = 8;
+pub const PINK072_ERR_INVALID_SEED: i32 = 9;
+pub const PINK072_ERR_ENTROPY: i32 = 10;
+pub const PINK072_ERR_PNG_CRC_MISMATCH: i32 = 11;
+pub const PINK072_ERR_PNG_MISSING_IHDR: i32 = 12;
+pub const PINK072_ERR_PNG_CHUNK_OVERFLOW: i32 = 13;
+pub const PINK072_ERR_PNG_MISSING_IEND: i32 = 14;
+pub const PINK072_ERR_UNSUPPORTED_PNG: i32 = 15;
+pub const PINK072_ERR_CORRUPT_IMAGE_DATA: i32 = 16;
+pub const PINK072_ERR_UNSUPPORTED_VERSION: i32 = 17;
+pub const PINK072_ERR_MISSING_IMAGE_END: i32 = 18;
+pub const PINK072_ERR_UNSIGNED: i32 = 19;
+pub const PINK072_ERR_UNTRUSTED_KEY: i32 = 20;
+pub const PINK072_ERR_BAD_SIGNATURE: i32 = 21;
+pub const PINK072_ERR_ENCRYPTED: i32 = 22;
+pub const PINK072_ERR_NO_RECIPIENTS: i32 = 23;
+pub const PINK072_ERR_INVALID_RECIPIENT_KEY: i32 = 24;
+pub const PINK072_ERR_NOT_RECIPIENT: i32 = 25;
+pub const PINK072_ERR_DECRYPTION_FAILED: i32 = 26;
+pub const PINK072_ERR_ENCRYPTION_FAILED: i32 = 27;
+/// 必須のポインタがNULL
+pub const PINK072_ERR_NULL_POINTER: i32 = 100;
+/// 引数が不正（UTF-8でないパスなど）
+pub const PINK072_ERR_INVALID_ARGUMENT: i32 = 101;
+/// ファイルの読み書きに失敗した
+pub const PINK072_ERR_IO: i32 = 102;
+/// 内部でパニックした
+pub const PINK072_ERR_PANIC: i32 = 103;
+
+/// Rustが確保したバイト列（`pink072_buffer_free` で解放する）
+#[repr(C)]
+pub struct Pink072Buffer {
+    pub data: *mut u8,
+    pub len: usize,
+}
+
+impl Pink072Buffer {
+    fn from_vec(data: Vec<u8>) -> Self {
+        let len = data.len();
+        let data = Box::into_raw(data.into_boxed_slice()) as *mut u8;
+        Pink072Buffer { data, len }
+    }
+}
+
+/// `PinkError` をエラーコードにする
+fn error_code(e: PinkError) -> i32 {
+    match e {
+        PinkError::SeedLength => PINK072_ERR_SEED_LENGTH,
+        PinkError::BufferTooSmall => PINK072_ERR_BUFFER_TOO_SMALL,
+        PinkError::FrameTooSmall => PINK072_ERR_FRAME_TOO_SMALL,
+        PinkError::PayloadLengthOverflow => PINK072_ERR_PAYLOAD_LENGTH_OVERFLOW,
+        PinkError::TruncatedFrame => PINK072_ERR_TRUNCATED_FRAME,
+        PinkError::InvalidFormat => PINK072_ERR_INVALID_FORMAT,
+        PinkError::CoverMismatch => PINK072_ERR_COVER_MISMATCH,
+        PinkError::InvalidCover => PINK072_ERR_INVALID_COVER,
+        PinkError::InvalidSeed => PINK072_ERR_INVALID_SEED,
+        PinkError::Entropy => PINK072_ERR_ENTROPY,
+        PinkError::PngCrcMismatch { .. } => PINK072_ERR_PNG_CRC_MISMATCH,
+        PinkError::PngMissingIhdr => PINK072_ERR_PNG_MISSING_IHDR,
+        PinkError::PngChunkOverflow { .. } => PINK072_ERR_PNG_CHUNK_OVERFLOW,
+        PinkError::PngMissingIend => PINK072_ERR_PNG_MISSING_IEND,
+        PinkError::UnsupportedPng => PINK072_ERR_UNSUPPORTED_PNG,
+        PinkError::CorruptImageData => PINK072_ERR_CORRUPT_IMAGE_DATA,
+        PinkError::UnsupportedVersion { .. } => PINK072_ERR_UNSUPPORTED_VERSION,
+        PinkError::MissingImageEnd => PINK072_ERR_MISSING_IMAGE_END,
+        PinkError::Unsigned => PINK072_ERR_UNSIGNED,
+        PinkError::UntrustedKey { .. } => PINK072_ERR_UNTRUSTED_KEY,
+        PinkError::BadSignature => PINK072_ERR_BAD_SIGNATURE,
+        PinkError::Encrypted => PINK072_ERR_ENCRYPTED,
+        PinkError::NoRecipients => PINK072_ERR_NO_RECIPIENTS,
+        PinkError::InvalidRecipientKey => PINK072_ERR_INVALID_RECIPIENT_KEY,
+        PinkError::NotRecipient => PINK072_ERR_NOT_RECIPIENT,
+        PinkError::DecryptionFailed => PINK072_ERR_DECRYPTION_FAILED,
+        PinkError::EncryptionFailed => PINK072_ERR_ENCRYPTION_FAILED,
+    }
+}
+
+/// パニックを `PINK072_ERR_PANIC` にして、C側に巻き戻さない
+fn guard(f: impl FnOnce() -> Result<(), i32> + UnwindSafe) -> i32 {
+    match catch_unwind(f) {
+        Ok(Ok(())) => PINK072_OK,
+        Ok(Err(code)) => code,
+        Err(_) => PINK072_ERR_PANIC,
+    }
+}
+
+/// `(ptr, len)` をスライスにする（`len` が0ならNULLでもよい）
+unsafe fn input<'a>(data: *const u8, len: usize) -> Result<&'a [u8], i32> {
+    if len == 0 {
+        Ok(&[])
+    } else if data.is_null() {
+        Err(PINK072_ERR_NULL_POINTER)
+    } else {
+        Ok(std::slice::from_raw_parts(data, len))
+    }
+}
+
+unsafe fn seed(seed9: *const u8) -> Result<Seed9, i32> {
+    if seed9.is_null() {
+        return Err(PINK072_ERR_NULL_POINTER);
+    }
+    Seed9::try_from(std::slice::from_raw_parts(seed9, 9)).map_err(error_code)
+}
+
+unsafe fn path<'a>(path: *const c_char) -> Result<&'a Path, i32> {
+    if path.is_null() {
+        return Err(PINK072_ERR_NULL_POINTER);
+    }
+    CStr::from_ptr(path)
+        .to_str()
+        .map(Path::new)
+        .map_err(|_| PINK072_ERR_INVALID_ARGUMENT)
+}
+
+unsafe fn output<'a>(out: *mut Pink072Buffer) -> Result<&'a mut Pink072Buffer, i32> {
+    out.as_mut().ok_or(PINK072_ERR_NULL_POINTER)
+}
+
+/// ファイル単位の関数の `io::Error` をエラーコードにする
+///
+/// フレームの誤りは `PinkError` と同じコード、それ以外は種類ごとのコードになる。
+fn io_code(e: std::io::Error) -> i32 {
+    let inner = e
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<PinkError>());
+    match inner {
+        Some(inner) => error_code(inner.clone()),
+        None => match e.kind() {
+            std::io::ErrorKind::InvalidData => PINK072_ERR_INVALID_FORMAT,
+            std::io::ErrorKind::InvalidInput => PINK072_ERR_INVALID_ARGUMENT,
+            _ => PINK072_ERR_IO,
+        },
+    }
+}
+
+/// ペイロードをフレームにする（`seed9` は9バイト）
+///
+/// # Safety
+/// 各ポインタは示す長さだけ読み書きできなければならない。
+#[no_mangle]
+pub unsafe extern "C" fn pink072_wrap(
+    payload: *const u8,
+    payload_len: usize,
+    payload_type: u8,
+    seed9: *const u8,
+    out_frame: *mut Pink072Buffer,
+) -> i32 {
+    guard(|| {
+        let payload = input(payload, payload_len)?;
+        let seed9 = seed(seed9)?;
+        let out_frame = output(out_frame)?;
+        let frame = crate::core::pink072_wrap(payload, payload_type, seed9).map_err(error_code)?;
+        *out_frame = Pink072Buffer::from_vec(frame);
+        Ok(())
+    })
+}
+
+/// フレームからペイロードを取り出す
+///
+/// # Safety
+/// 各ポインタは示す長さだけ読み書きできなければならない。
+#[no_mangle]
+pub unsafe extern "C" fn pink072_unwrap(
+    frame: *const u8,
+    frame_len: usize,
+    out_payload_type: *mut u8,
+    out_payload: *mut Pink072Buffer,
+) -> i32 {
+    guard(|| {
+        let frame = input(frame, frame_len)?;
+        if out_payload_type.is_null() {
+            return Err(PINK072_ERR_NULL_POINTER);
+        }
+        let out_payload = output(out_payload)?;
+        let (payload_type, payload) = crate::core::pink072_unwrap(frame).map_err(error_code)?;
+        *out_payload_type = payload_type;
+        *out_payload = Pink072Buffer::from_vec(payload);
+        Ok(())
+    })
+}
+
+/// フレームをPNK（PNG）にする
+///
+/// # Safety
+/// 各ポインタは示す長さだけ読み書きできなければならない。
+#[no_mangle]
+pub unsafe extern "C" fn pink072_encode_pnk(
+    frame: *const u8,
+    frame_len: usize,
+    out_pnk: *mut Pink072Buffer,
+) -> i32 {
+    guard(|| {
+        let frame = input(frame, frame_len)?;
+        let out_pnk = output(out_pnk)?;
+        *out_pnk = Pink072Buffer::from_vec(encode_pnk(frame).map_err(error_code)?);
+        Ok(())
+    })
+}
+
+/// PNKからフレームを取り出す
+///
+/// # Safety
+/// 各ポインタは示す長さだけ読み書きできなければならない。
+#[no_mangle]
+pub unsafe extern "C" fn pink072_decode_pnk(
+    pnk: *const u8,
+    pnk_len: usize,
+    out_frame: *mut Pink072Buffer,
+) -> i32 {
+    guard(|| {
+        let pnk = input(pnk, pnk_len)?;
+        let out_frame = output(out_frame)?;
+        let frame = decode_pnk(pnk).map_err(error_code)?;
+        *out_frame = Pink072Buffer::from_vec(frame.into_owned());
+        Ok(())
+    })
+}
+
+/// ファイルまたはフォルダをPNKファイルにする（`encode_auto`）
+///
+/// # Safety
+/// パスはNUL終端のUTF-8文字列、`seed9` は9バイトでなければならない。
+#[no_mangle]
+pub unsafe extern "C" fn pink072_encode_file(
+    input_path: *const c_char,
+    output_path: *const c_char,
+    seed9: *const u8,
+) -> i32 {
+    guard(|| {
+        let input_path = path(input_path)?;
+        let output_path = path(output_path)?;
+        let seed9 = seed(seed9)?;
+        encode_auto(input_path, output_path, seed9).map_err(io_code)
+    })
+}
+
+/// PNKファイルを `output_dir` に展開する（`decode_auto`）
+///
+/// # Safety
+/// パスはNUL終端のUTF-8文字列でなければならない。
+#[no_mangle]
+pub unsafe extern "C" fn pink072_decode_file(
+    input_path: *const c_char,
+    output_dir: *const c_char,
+) -> i32 {
+    guard(|| {
+        let input_path = path(input_path)?;
+        let output_dir = path(output_dir)?;
+        decode_auto(input_path, output_dir)
+            .map(|_| ())
+            .map_err(io_code)
+    })
+}
+
+/// このライブラリが返したバッファを解放する（空のバッファは何もしない）
+///
+/// # Safety
+/// `buffer` はこのライブラリが返したもので、まだ解放していないものでなければならない。
+#[no_mangle]
+pub unsafe extern "C" fn pink072_buffer_free(buffer: Pink072Buffer) {
+    if !buffer.data.is_null() {
+        drop(Box::from_raw(ptr::slice_from_raw_parts_mut(
+            buffer.data,
+            buffer.len,
+        )));
+    }
+}
+
+/// エラーコードの説明（静的な文字列、解放しない）
+#[no_mangle]
+pub extern "C" fn pink072_error_message(code: i32) -> *const c_char {
+    let message: &'static CStr = match code {
+        PINK072_OK => c"ok",
+        PINK072_ERR_SEED_LENGTH => c"seed must be exactly 9 bytes",
+        PINK072_ERR_BUFFER_TOO_SMALL => c"buffer too small",
+        PINK072_ERR_FRAME_TOO_SMALL => c"frame too small",
+        PINK072_ERR_PAYLOAD_LENGTH_OVERFLOW => c"payload length overflow",
+        PINK072_ERR_TRUNCATED_FRAME => c"truncated frame",
+        PINK072_ERR_INVALID_FORMAT => c"invalid PNK format",
+        PINK072_ERR_COVER_MISMATCH => c"cover does not match the expected seed",
+        PINK072_ERR_INVALID_COVER => c"invalid cover dimensions",
+        PINK072_ERR_INVALID_SEED => c"invalid seed string",
+        PINK072_ERR_ENTROPY => c"failed to obtain OS entropy",
+        PINK072_ERR_PNG_CRC_MISMATCH => c"bad CRC in PNG chunk",
+        PINK072_ERR_PNG_MISSING_IHDR => c"PNG does not start with an IHDR chunk",
+        PINK072_ERR_PNG_CHUNK_OVERFLOW => c"PNG chunk length overflow",
+        PINK072_ERR_PNG_MISSING_IEND => c"PNG ends without an IEND chunk",
+        PINK072_ERR_UNSUPPORTED_PNG => c"unsupported PNG",
+        PINK072_ERR_CORRUPT_IMAGE_DATA => c"corrupt PNG image data",
+        PINK072_ERR_UNSUPPORTED_VERSION => c"unsupported frame version",
+        PINK072_ERR_MISSING_IMAGE_END => c"image data ends before its end marker",
+        PINK072_ERR_UNSIGNED => c"frame is not signed",
+        PINK072_ERR_UNTRUSTED_KEY => c"frame is signed by an untrusted key",
+        PINK072_ERR_BAD_SIGNATURE => c"frame signature does not verify",
+        PINK072_ERR_ENCRYPTED => c"frame is encrypted; a secret key is required",
+        PINK072_ERR_NO_RECIPIENTS => c"at least one recipient is required",
+        PINK072_ERR_INVALID_RECIPIENT_KEY => c"invalid recipient public key",
+        PINK072_ERR_NOT_RECIPIENT => c"frame is not encrypted for this key",
+        PINK072_ERR_DECRYPTION_FAILED => c"failed to decrypt the payload",
+        PINK072_ERR_ENCRYPTION_FAILED => c"failed to encrypt the payload",
+        PINK072_ERR_NULL_POINTER => c"null pointer argument",
+        PINK072_ERR_INVALID_ARGUMENT => c"invalid argument",
+        PINK072_ERR_IO => c"I/O error",
+        PINK072_ERR_PANIC => c"internal panic",
+        _ => c"unknown error",
+    };
+    message.as_ptr()
+}
@@ -1,9 +1,19 @@
 //! ファイル/フォルダのエンコード・デコード機能
 
-use crate::{decode_pnk, encode_pnk, pink072_unwrap, pink072_wrap};
+use crate::constants::HEADER_LEN;
+use crate::core::FrameHeader;
+use crate::encrypt::{pink072_unwrap_encrypted, pink072_wrap_encrypted};
+use crate::png::PNG_SIGNATURE;
+use crate::sign::{verify_frame, Keyring};
+use crate::{
+    decode_carrier, encode_pnk, pink072_unwrap, pink072_wrap, PnkInfo, PnkLayout, Seed9,
+    PNK_CHUNK_TYPE,
+};
+use std::fmt;
 use std::fs::{self, File};
-use std::io::{self, Cursor, Read, Write};
-use std::path::Path;
+use std::io::{self, Cursor, Read, Seek, SeekFrom, Write};
+use std::path::{Component, Path, PathBuf};
+use x25519_dalek::{PublicKey, StaticSecret};
 use zip::write::SimpleFileOptions;
 use zip::{ZipArchive, ZipWriter};
 
@@ -15,7 +25,21 @@ pub const PAYLOAD_TYPE_ZIP: u8 = 2;
 /// 単一ファイルをPNKにエンコード
 ///
 /// ペイロード構造: `[ファイル名長 2B (LE)][ファイル名 UTF-8][データ]`
-pub fn encode_file(input_path: &Path, output_path: &Path, seed9: &[u8; 9]) -> io::Result<()> {
+pub fn encode_file(
+    input_path: &Path,
+    output_path: &Path,
+    seed9: impl Into<Seed9>,
+) -> io::Result<()> {
+    encode_file_with(input_path, output_path, seed9, &EncodeOptions::default())
+}
+
+/// オプションを指定して単一ファイルをPNKにエンコード
+pub fn encode_file_with(
+    input_path: &Path,
+    output_path: &Path,
+    seed9: impl Into<Seed9>,
+    options: &EncodeOptions<'_>,
+) -> io::Result<()> {
     let file_name = input_path
         .file_name()
         .and_then(|n| n.to_str())
@@ -24,23 +48,28 @@ pub fn encode_file(input_path: &Path, output_path: &Path, seed9: &[u8; 9]) -> io
     let file_data = fs::read(input_path)?;
     let payload = build_file_payload(file_name, &file_data);
 
-    let frame = pink072_wrap(&payload, PAYLOAD_TYPE_FILE, seed9)
-        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    let frame = wrap_frame(&payload, PAYLOAD_TYPE_FILE, seed9, options)?;
 
-    let pnk = encode_pnk(&frame);
+    let pnk = encode_pnk(&frame).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
     fs::write(output_path, pnk)?;
 
     Ok(())
 }
 
 /// PNKから単一ファイルをデコード
+///
+/// JPEG・GIF・BMP・WebPのキャリアも形式を自動判定して読める。
 pub fn decode_file(input_path: &Path, output_dir: &Path) -> io::Result<String> {
-    let pnk_data = fs::read(input_path)?;
-    let frame = decode_pnk(&pnk_data)
-        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    decode_file_with(input_path, output_dir, &DecodeOptions::default())
+}
 
-    let (payload_type, payload) = pink072_unwrap(frame)
-        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+/// オプションを指定してPNKから単一ファイルをデコード
+pub fn decode_file_with(
+    input_path: &Path,
+    output_dir: &Path,
+    options: &DecodeOptions<'_>,
+) -> io::Result<String> {
+    let (payload_type, payload) = read_frame(input_path, options)?;
 
     if payload_type != PAYLOAD_TYPE_FILE {
         return Err(io::Error::new(
@@ -51,15 +80,15 @@ pub fn decode_file(input_path: &Path, output_dir: &Path) -> io::Result<String> {
 
     let (file_name, file_data) = parse_file_payload(&payload)?;
 
+    let output_path = output_file_path(output_dir, &file_name)?;
     fs::create_dir_all(output_dir)?;
-    let output_path = output_dir.join(&file_name);
     fs::write(&output_path, file_data)?;
 
     Ok(file_name)
 }
 
 /// ファイル名とデータからペイロードを構築
-fn build_file_payload(file_name: &str, data: &[u8]) -> Vec<u8> {
+pub(crate) fn build_file_payload(file_name: &str, data: &[u8]) -> Vec<u8> {
     let name_bytes = file_name.as_bytes();
     let name_len = name_bytes.len() as u16;
 
@@ -71,7 +100,7 @@ fn build_file_payload(file_name: &str, data: &[u8]) -> Vec<u8> {
 }
 
 /// ペイロードからファイル名とデータを抽出
-fn parse_file_payload(payload: &[u8]) -> io::Result<(String, &[u8])> {
+pub(crate) fn parse_file_payload(payload: &[u8]) -> io::Result<(String, &[u8])> {
     if payload.len() < 2 {
         return Err(io::Error::new(
             io::ErrorKind::InvalidData,
@@ -95,25 +124,51 @@ fn parse_file_payload(payload: &[u8]) -> io::Result<(String, &[u8])> {
     Ok((file_name, data))
 }
 
+/// ペイロードのファイル名から展開先のパスを作る
+///
+/// ファイル名は区切り文字を含まない1要素に限る。`..` や絶対パスのように
+/// `output_dir` の外を指す名前は `InvalidData` で拒否する。
+pub(crate) fn output_file_path(output_dir: &Path, file_name: &str) -> io::Result<PathBuf> {
+    let mut components = Path::new(file_name).components();
+    match (components.next(), components.next()) {
+        (Some(Component::Normal(name)), None) if !file_name.contains(['/', '\\']) => {
+            Ok(output_dir.join(name))
+        }
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("unsafe file name: {file_name:?}"),
+        )),
+    }
+}
+
 /// 生データをPNKにエンコード（ファイル名なし）
-pub fn encode_raw(data: &[u8], output_path: &Path, seed9: &[u8; 9]) -> io::Result<()> {
-    let frame = pink072_wrap(data, PAYLOAD_TYPE_RAW, seed9)
-        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+pub fn encode_raw(data: &[u8], output_path: &Path, seed9: impl Into<Seed9>) -> io::Result<()> {
+    encode_raw_with(data, output_path, seed9, &EncodeOptions::default())
+}
 
-    let pnk = encode_pnk(&frame);
+/// オプションを指定して生データをPNKにエンコード
+pub fn encode_raw_with(
+    data: &[u8],
+    output_path: &Path,
+    seed9: impl Into<Seed9>,
+    options: &EncodeOptions<'_>,
+) -> io::Result<()> {
+    let frame = wrap_frame(data, PAYLOAD_TYPE_RAW, seed9, options)?;
+
+    let pnk = encode_pnk(&frame).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
     fs::write(output_path, pnk)?;
 
     Ok(())
 }
 
-/// PNKから生データをデコード
+/// PNKから生データをデコード（キャリアの形式は自動判定）
 pub fn decode_raw(input_path: &Path) -> io::Result<Vec<u8>> {
-    let pnk_data = fs::read(input_path)?;
-    let frame = decode_pnk(&pnk_data)
-        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    decode_raw_with(input_path, &DecodeOptions::default())
+}
 
-    let (payload_type, payload) = pink072_unwrap(frame)
-        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+/// オプションを指定してPNKから生データをデコード
+pub fn decode_raw_with(input_path: &Path, options: &DecodeOptions<'_>) -> io::Result<Vec<u8>> {
+    let (payload_type, payload) = read_frame(input_path, options)?;
 
     if payload_type != PAYLOAD_TYPE_RAW {
         return Err(io::Error::new(
@@ -126,26 +181,43 @@ pub fn decode_raw(input_path: &Path) -> io::Result<Vec<u8>> {
 }
 
 /// フォルダをZIP化してPNKにエンコード
-pub fn encode_folder(input_path: &Path, output_path: &Path, seed9: &[u8; 9]) -> io::Result<()> {
+pub fn encode_folder(
+    input_path: &Path,
+    output_path: &Path,
+    seed9: impl Into<Seed9>,
+) -> io::Result<()> {
+    encode_folder_with(input_path, output_path, seed9, &EncodeOptions::default())
+}
+
+/// オプションを指定してフォルダをZIP化してPNKにエンコード
+pub fn encode_folder_with(
+    input_path: &Path,
+    output_path: &Path,
+    seed9: impl Into<Seed9>,
+    options: &EncodeOptions<'_>,
+) -> io::Result<()> {
     let zip_data = create_zip_from_folder(input_path)?;
 
-    let frame = pink072_wrap(&zip_data, PAYLOAD_TYPE_ZIP, seed9)
-        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    let frame = wrap_frame(&zip_data, PAYLOAD_TYPE_ZIP, seed9, options)?;
 
-    let pnk = encode_pnk(&frame);
+    let pnk = encode_pnk(&frame).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
     fs::write(output_path, pnk)?;
 
     Ok(())
 }
 
-/// PNKからフォルダをデコード（ZIP展開）
+/// PNKからフォルダをデコード（ZIP展開、キャリアの形式は自動判定）
 pub fn decode_folder(input_path: &Path, output_dir: &Path) -> io::Result<Vec<String>> {
-    let pnk_data = fs::read(input_path)?;
-    let frame = decode_pnk(&pnk_data)
-        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    decode_folder_with(input_path, output_dir, &DecodeOptions::default())
+}
 
-    let (payload_type, payload) = pink072_unwrap(frame)
-        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+/// オプションを指定してPNKからフォルダをデコード
+pub fn decode_folder_with(
+    input_path: &Path,
+    output_dir: &Path,
+    options: &DecodeOptions<'_>,
+) -> io::Result<Vec<String>> {
+    let (payload_type, payload) = read_frame(input_path, options)?;
 
     if payload_type != PAYLOAD_TYPE_ZIP {
         return Err(io::Error::new(
@@ -158,7 +230,7 @@ pub fn decode_folder(input_path: &Path, output_dir: &Path) -> io::Result<Vec<Str
 }
 
 /// フォルダからZIPを作成
-fn create_zip_from_folder(folder_path: &Path) -> io::Result<Vec<u8>> {
+pub(crate) fn create_zip_from_folder(folder_path: &Path) -> io::Result<Vec<u8>> {
     let mut buffer = Cursor::new(Vec::new());
     {
         let mut zip = ZipWriter::new(&mut buffer);
@@ -200,15 +272,94 @@ fn add_folder_to_zip<W: Write + io::Seek>(
     Ok(())
 }
 
+/// ZIPの1エントリの情報
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ZipEntry {
+    /// アーカイブ内のパス（区切りは `/`）
+    pub path: String,
+    /// 展開後のサイズ
+    pub size: u64,
+    pub is_dir: bool,
+}
+
+impl ZipEntry {
+    pub(crate) fn of<R: Read>(file: &zip::read::ZipFile<'_, R>) -> Self {
+        ZipEntry {
+            path: file.name().to_string(),
+            size: file.size(),
+            is_dir: file.is_dir(),
+        }
+    }
+}
+
+/// `(パス, データ)` の並びからZIPペイロードをメモリ上で構築する（ファイルシステム不要）
+///
+/// パスは `/` 区切りの相対パス。空のパス、絶対パス、`..` を含むパスは拒否する。
+pub fn build_zip_payload<'a>(
+    entries: impl IntoIterator<Item = (&'a str, &'a [u8])>,
+) -> io::Result<Vec<u8>> {
+    let mut buffer = Cursor::new(Vec::new());
+    {
+        let mut zip = ZipWriter::new(&mut buffer);
+        let options =
+            SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+
+        for (path, data) in entries {
+            let is_relative = !path.is_empty()
+                && !path.starts_with('/')
+                && path
+                    .split(['/', '\\'])
+                    .all(|part| !part.is_empty() && part != "." && part != "..");
+            if !is_relative {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("invalid entry path: {path}"),
+                ));
+            }
+            zip.start_file(path, options)?;
+            zip.write_all(data)?;
+        }
+        zip.finish()?;
+    }
+    Ok(buffer.into_inner())
+}
+
+/// ZIPペイロードのエントリ一覧を返す（データは展開しない）
+pub fn list_zip_entries(zip_data: &[u8]) -> io::Result<Vec<ZipEntry>> {
+    let mut archive = ZipArchive::new(Cursor::new(zip_data))?;
+    (0..archive.len())
+        .map(|i| Ok(ZipEntry::of(&archive.by_index_raw(i)?)))
+        .collect()
+}
+
+/// ZIPペイロードから1エントリのデータを取り出す
+pub fn read_zip_entry(zip_data: &[u8], path: &str) -> io::Result<Vec<u8>> {
+    let mut archive = ZipArchive::new(Cursor::new(zip_data))?;
+    let mut file = archive.by_name(path)?;
+    // 宣言されたサイズは信用できないので、先に確保しない
+    let mut data = Vec::new();
+    file.read_to_end(&mut data)?;
+    Ok(data)
+}
+
 /// ZIPをフォルダに展開
-fn extract_zip_to_folder(zip_data: &[u8], output_dir: &Path) -> io::Result<Vec<String>> {
+pub(crate) fn extract_zip_to_folder(zip_data: &[u8], output_dir: &Path) -> io::Result<Vec<String>> {
     let cursor = Cursor::new(zip_data);
     let mut archive = ZipArchive::new(cursor)?;
     let mut extracted_files = Vec::new();
 
     for i in 0..archive.len() {
         let mut file = archive.by_index(i)?;
-        let outpath = output_dir.join(file.mangled_name());
+        // `..` や絶対パスで展開先の外を指すエントリは拒否する
+        let outpath = match file.enclosed_name() {
+            Some(path) => output_dir.join(path),
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("unsafe entry path: {:?}", file.name()),
+                ))
+            }
+        };
 
         if file.is_dir() {
             fs::create_dir_all(&outpath)?;
@@ -226,22 +377,109 @@ fn extract_zip_to_folder(zip_data: &[u8], output_dir: &Path) -> io::Result<Vec<S
 }
 
 /// ファイルまたはフォルダを自動判定してエンコード
-pub fn encode_auto(input_path: &Path, output_path: &Path, seed9: &[u8; 9]) -> io::Result<()> {
+pub fn encode_auto(
+    input_path: &Path,
+    output_path: &Path,
+    seed9: impl Into<Seed9>,
+) -> io::Result<()> {
+    encode_auto_with(input_path, output_path, seed9, &EncodeOptions::default())
+}
+
+/// オプションを指定してファイルまたはフォルダを自動判定してエンコード
+pub fn encode_auto_with(
+    input_path: &Path,
+    output_path: &Path,
+    seed9: impl Into<Seed9>,
+    options: &EncodeOptions<'_>,
+) -> io::Result<()> {
     if input_path.is_dir() {
-        encode_folder(input_path, output_path, seed9)
+        encode_folder_with(input_path, output_path, seed9, options)
     } else {
-        encode_file(input_path, output_path, seed9)
+        encode_file_with(input_path, output_path, seed9, options)
     }
 }
 
-/// PNKを自動判定してデコード
-pub fn decode_auto(input_path: &Path, output_dir: &Path) -> io::Result<Vec<String>> {
+/// `encode_*_with` のオプション
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EncodeOptions<'a> {
+    /// 空でなければ、ペイロードをこの受信者の公開鍵で暗号化する
+    pub recipients: &'a [PublicKey],
+}
+
+/// `decode_*_with` のオプション
+#[derive(Clone, Copy, Default)]
+pub struct DecodeOptions<'a> {
+    /// 指定すると、この鍵束の鍵で正しく署名されたフレームだけを受け付ける
+    /// （署名の無いフレーム、信頼していない鍵のフレームは拒否する）
+    pub trusted_keys: Option<&'a Keyring>,
+    /// 暗号化されたフレームを復号する秘密鍵
+    pub secret_key: Option<&'a StaticSecret>,
+}
+
+impl fmt::Debug for DecodeOptions<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // 秘密鍵は表示しない
+        f.debug_struct("DecodeOptions")
+            .field("trusted_keys", &self.trusted_keys)
+            .field("secret_key", &self.secret_key.map(|_| "<redacted>"))
+            .finish()
+    }
+}
+
+/// オプションに従ってフレームを生成する（受信者があれば暗号化）
+pub(crate) fn wrap_frame(
+    payload: &[u8],
+    payload_type: u8,
+    seed9: impl Into<Seed9>,
+    options: &EncodeOptions<'_>,
+) -> io::Result<Vec<u8>> {
+    let frame = if options.recipients.is_empty() {
+        pink072_wrap(payload, payload_type, seed9)
+    } else {
+        pink072_wrap_encrypted(payload, payload_type, seed9, options.recipients)
+    };
+    frame.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// PNKを読み、オプションに従って署名を検証・復号してペイロードを取り出す
+///
+/// フレームの誤りは `PinkError` を中身に持つ `InvalidData` になる（`get_ref` で取り出せる）。
+fn read_frame(input_path: &Path, options: &DecodeOptions<'_>) -> io::Result<(u8, Vec<u8>)> {
     let pnk_data = fs::read(input_path)?;
-    let frame = decode_pnk(&pnk_data)
-        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    open_frame(&pnk_data, options)
+}
+
+/// 読み込んだPNKから `read_frame` と同じ手順でペイロードを取り出す
+pub(crate) fn open_frame(
+    pnk_data: &[u8],
+    options: &DecodeOptions<'_>,
+) -> io::Result<(u8, Vec<u8>)> {
+    let (_, frame) =
+        decode_carrier(pnk_data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
 
-    let (payload_type, payload) = pink072_unwrap(frame)
-        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string()))?;
+    if let Some(keyring) = options.trusted_keys {
+        verify_frame(&frame, keyring).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    }
+
+    match options.secret_key {
+        Some(secret_key) => pink072_unwrap_encrypted(&frame, secret_key),
+        None => pink072_unwrap(&frame),
+    }
+    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// PNKを自動判定してデコード（キャリアの形式も自動判定）
+pub fn decode_auto(input_path: &Path, output_dir: &Path) -> io::Result<Vec<String>> {
+    decode_auto_with(input_path, output_dir, &DecodeOptions::default())
+}
+
+/// オプションを指定してPNKを自動判定してデコード
+pub fn decode_auto_with(
+    input_path: &Path,
+    output_dir: &Path,
+    options: &DecodeOptions<'_>,
+) -> io::Result<Vec<String>> {
+    let (payload_type, payload) = read_frame(input_path, options)?;
 
     match payload_type {
         PAYLOAD_TYPE_RAW => {
@@ -252,8 +490,8 @@ pub fn decode_auto(input_path: &Path, output_dir: &Path) -> io::Result<Vec<Strin
         }
         PAYLOAD_TYPE_FILE => {
             let (file_name, file_data) = parse_file_payload(&payload)?;
+            let output_file = output_file_path(output_dir, &file_name)?;
             fs::create_dir_all(output_dir)?;
-            let output_file = output_dir.join(&file_name);
             fs::write(&output_file, file_data)?;
             Ok(vec![file_name])
         }
@@ -264,3 +502,38 @@ pub fn decode_auto(input_path: &Path, output_dir: &Path) -> io::Result<Vec<Strin
         )),
     }
 }
+
+/// ファイルがPNKかどうかを安価に判定する
+///
+/// PNGのチャンクを長さだけ見て読み飛ばし（CRCは検証しない）、
+/// フレームヘッダ（先頭32バイト）だけを読む。ペイロードは読まない。
+pub fn detect_pnk(path: &Path) -> Option<PnkInfo> {
+    let mut file = File::open(path).ok()?;
+    let mut signature = [0u8; 8];
+    file.read_exact(&mut signature).ok()?;
+    if signature != PNG_SIGNATURE {
+        return None;
+    }
+
+    let mut header = [0u8; HEADER_LEN];
+    loop {
+        let mut chunk_head = [0u8; 8];
+        file.read_exact(&mut chunk_head).ok()?;
+        let len = u32::from_be_bytes([chunk_head[0], chunk_head[1], chunk_head[2], chunk_head[3]]);
+        let chunk_type = &chunk_head[4..8];
+
+        let layout = if chunk_type == PNK_CHUNK_TYPE && len as usize >= HEADER_LEN {
+            PnkLayout::Chunk
+        } else if chunk_type == b"IEND" {
+            file.seek(SeekFrom::Current(len as i64 + 4)).ok()?;
+            PnkLayout::Trailing
+        } else {
+            file.seek(SeekFrom::Current(len as i64 + 4)).ok()?;
+            continue;
+        };
+
+        file.read_exact(&mut header).ok()?;
+        let frame = FrameHeader::sniff(&header)?.info();
+        return Some(PnkInfo { layout, frame });
+    }
+}
@@ -0,0 +1,261 @@
+//! 最小限のGIFエンコーダ（単一フレーム、LZW）と終端検出
+
+use alloc::{collections::BTreeMap, vec::Vec};
+
+use crate::error::PinkError;
+
+/// GIFのシグネチャ（GIF87a / GIF89a 共通部分）
+pub(crate) const GIF_SIGNATURE: [u8; 4] = *b"GIF8";
+
+const EXTENSION: u8 = 0x21;
+const IMAGE_DESCRIPTOR: u8 = 0x2C;
+const TRAILER: u8 = 0x3B;
+
+/// LZWの最大符号長
+const LZW_MAX_BITS: u8 = 12;
+
+/// 任意サイズのRGBAをGIFにエンコード
+///
+/// 色数が256以下（透明を含む）ならそのまま、超える場合は
+/// 固定の252色パレット（6x7x6）に減色する。
+/// アルファが128未満の画素は透明になる。幅・高さは 1〜65535（範囲外は `InvalidCover`）。
+pub fn encode_gif_rgba(rgba: &[u8], width: usize, height: usize) -> Result<Vec<u8>, PinkError> {
+    if width == 0 || height == 0 || width > 0xFFFF || height > 0xFFFF {
+        return Err(PinkError::InvalidCover);
+    }
+    debug_assert_eq!(rgba.len(), width * height * 4);
+
+    let (palette, indices, transparent) = build_palette(rgba);
+
+    // パレットは2のべき乗（最小2色）に揃える
+    let table_bits = (usize::BITS - (palette.len().max(2) - 1).leading_zeros()) as u8;
+    let mut table = palette;
+    table.resize(1 << table_bits, [0, 0, 0]);
+
+    let mut out = Vec::new();
+    out.extend_from_slice(b"GIF89a");
+    out.extend_from_slice(&(width as u16).to_le_bytes());
+    out.extend_from_slice(&(height as u16).to_le_bytes());
+    out.push(0x80 | 0x70 | (table_bits - 1)); // グローバルカラーテーブルあり、8bit色
+    out.push(0); // 背景色
+    out.push(0); // アスペクト比
+    for color in &table {
+        out.extend_from_slice(color);
+    }
+
+    if let Some(index) = transparent {
+        // Graphic Control Extension（透明色のみ指定）
+        out.extend_from_slice(&[EXTENSION, 0xF9, 4, 0x01, 0, 0, index, 0]);
+    }
+
+    out.push(IMAGE_DESCRIPTOR);
+    out.extend_from_slice(&[0, 0, 0, 0]);
+    out.extend_from_slice(&(width as u16).to_le_bytes());
+    out.extend_from_slice(&(height as u16).to_le_bytes());
+    out.push(0);
+
+    let min_code_size = table_bits.max(2);
+    out.push(min_code_size);
+    for block in lzw_compress(&indices, min_code_size).chunks(255) {
+        out.push(block.len() as u8);
+        out.extend_from_slice(block);
+    }
+    out.push(0);
+
+    out.push(TRAILER);
+    Ok(out)
+}
+
+/// パレットと各画素のインデックス、透明色のインデックスを求める
+fn build_palette(rgba: &[u8]) -> (Vec<[u8; 3]>, Vec<u8>, Option<u8>) {
+    let mut palette = Vec::new();
+    let mut lookup: BTreeMap<[u8; 3], u8> = BTreeMap::new();
+    let mut transparent = None;
+    let mut indices = Vec::with_capacity(rgba.len() / 4);
+
+    for pixel in rgba.chunks_exact(4) {
+        let index = if pixel[3] < 128 {
+            *transparent.get_or_insert_with(|| {
+                palette.push([0, 0, 0]);
+                (palette.len() - 1) as u8
+            })
+        } else {
+            let color = [pixel[0], pixel[1], pixel[2]];
+            match lookup.get(&color) {
+                Some(&index) => index,
+                None if palette.len() < 256 => {
+                    palette.push(color);
+                    let index = (palette.len() - 1) as u8;
+                    lookup.insert(color, index);
+                    index
+                }
+                None => return build_fixed_palette(rgba),
+            }
+        };
+        if palette.len() > 256 {
+            return build_fixed_palette(rgba);
+        }
+        indices.push(index);
+    }
+    (palette, indices, transparent)
+}
+
+/// 固定の252色パレット（R6 x G7 x B6）に減色する。透明色は 252 番
+fn build_fixed_palette(rgba: &[u8]) -> (Vec<[u8; 3]>, Vec<u8>, Option<u8>) {
+    let level = |value: u32, steps: u32| (value * (steps - 1) + 127) / 255;
+    let mut palette = Vec::with_capacity(253);
+    for r in 0..6 {
+        for g in 0..7 {
+            for b in 0..6 {
+                palette.push([
+                    (r * 255 / 5) as u8,
+                    (g * 255 / 6) as u8,
+                    (b * 255 / 5) as u8,
+                ]);
+            }
+        }
+    }
+
+    let mut transparent = None;
+    let indices = rgba
+        .chunks_exact(4)
+        .map(|pixel| {
+            if pixel[3] < 128 {
+                transparent = Some(252);
+                252
+            } else {
+                let r = level(pixel[0] as u32, 6);
+                let g = level(pixel[1] as u32, 7);
+                let b = level(pixel[2] as u32, 6);
+                (r * 42 + g * 6 + b) as u8
+            }
+        })
+        .collect();
+    if transparent.is_some() {
+        palette.push([0, 0, 0]);
+    }
+    (palette, indices, transparent)
+}
+
+/// GIF用のLZW圧縮（LSBから詰める可変長符号）
+fn lzw_compress(indices: &[u8], min_code_size: u8) -> Vec<u8> {
+    let clear = 1u16 << min_code_size;
+    let end = clear + 1;
+
+    let mut writer = LzwWriter::default();
+    let mut dictionary: BTreeMap<(u16, u8), u16> = BTreeMap::new();
+    let mut next_code = clear + 2;
+    let mut code_size = min_code_size + 1;
+    writer.write(clear, code_size);
+
+    let Some((&first, rest)) = indices.split_first() else {
+        writer.write(end, code_size);
+        return writer.finish();
+    };
+
+    let mut prefix = first as u16;
+    for &index in rest {
+        if let Some(&code) = dictionary.get(&(prefix, index)) {
+            prefix = code;
+            continue;
+        }
+
+        writer.write(prefix, code_size);
+        if next_code < 1 << LZW_MAX_BITS {
+            dictionary.insert((prefix, index), next_code);
+            next_code += 1;
+            // デコーダは符号を追加した直後に符号長を伸ばす
+            if next_code > 1 << code_size && code_size < LZW_MAX_BITS {
+                code_size += 1;
+            }
+        } else {
+            // 辞書が一杯になったらリセット
+            writer.write(clear, code_size);
+            dictionary.clear();
+            next_code = clear + 2;
+            code_size = min_code_size + 1;
+        }
+        prefix = index as u16;
+    }
+    writer.write(prefix, code_size);
+    writer.write(end, code_size);
+    writer.finish()
+}
+
+/// LSBから詰めるビットライタ
+#[derive(Default)]
+struct LzwWriter {
+    out: Vec<u8>,
+    acc: u32,
+    count: u8,
+}
+
+impl LzwWriter {
+    fn write(&mut self, code: u16, len: u8) {
+        self.acc |= (code as u32) << self.count;
+        self.count += len;
+        while self.count >= 8 {
+            self.out.push(self.acc as u8);
+            self.acc >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.out.push(self.acc as u8);
+        }
+        self.out
+    }
+}
+
+/// GIFの終端（トレーラ `0x3B` の直後）を探す
+///
+/// 論理画面記述子とカラーテーブルを読み飛ばし、拡張ブロックと
+/// 画像データはサブブロックを辿って進める。
+pub fn find_gif_end(data: &[u8]) -> Result<usize, PinkError> {
+    if !data.starts_with(b"GIF87a") && !data.starts_with(b"GIF89a") {
+        return Err(PinkError::InvalidFormat);
+    }
+    let screen = data.get(6..13).ok_or(PinkError::MissingImageEnd)?;
+    let mut pos = 13 + color_table_len(screen[4]);
+
+    loop {
+        match *data.get(pos).ok_or(PinkError::MissingImageEnd)? {
+            TRAILER => return Ok(pos + 1),
+            EXTENSION => {
+                // ラベルの後はサブブロック
+                pos = skip_sub_blocks(data, pos + 2)?;
+            }
+            IMAGE_DESCRIPTOR => {
+                let descriptor = data
+                    .get(pos + 1..pos + 10)
+                    .ok_or(PinkError::MissingImageEnd)?;
+                // ローカルカラーテーブル、LZW最小符号長の後はサブブロック
+                pos += 10 + color_table_len(descriptor[8]) + 1;
+                pos = skip_sub_blocks(data, pos)?;
+            }
+            _ => return Err(PinkError::InvalidFormat),
+        }
+    }
+}
+
+/// パックドフィールドが示すカラーテーブルのバイト数
+fn color_table_len(packed: u8) -> usize {
+    if packed & 0x80 != 0 {
+        3 << ((packed & 0x07) + 1)
+    } else {
+        0
+    }
+}
+
+/// 長さ0のブロックで終わるサブブロック列を読み飛ばす
+fn skip_sub_blocks(data: &[u8], mut pos: usize) -> Result<usize, PinkError> {
+    loop {
+        let len = *data.get(pos).ok_or(PinkError::MissingImageEnd)? as usize;
+        pos += 1 + len;
+        if len == 0 {
+            return Ok(pos);
+        }
+    }
+}
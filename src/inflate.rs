@@ -0,0 +1,296 @@
+//! 最小限のzlib/deflate展開（RFC 1950 / RFC 1951）
+
+use alloc::{vec, vec::Vec};
+
+use crate::error::PinkError;
+use crate::png::adler32;
+
+/// 長さ符号（257〜285）の基本値と追加ビット数
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+
+/// 距離符号（0〜29）の基本値と追加ビット数
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// 符号長符号の並び順
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+const MAX_BITS: usize = 15;
+
+/// LSBファーストのビット読み出し
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    bit_buf: u32,
+    bit_count: u32,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        BitReader {
+            data,
+            pos: 0,
+            bit_buf: 0,
+            bit_count: 0,
+        }
+    }
+
+    fn bits(&mut self, n: u32) -> Result<u32, PinkError> {
+        while self.bit_count < n {
+            let byte = *self.data.get(self.pos).ok_or(PinkError::CorruptImageData)?;
+            self.pos += 1;
+            self.bit_buf |= (byte as u32) << self.bit_count;
+            self.bit_count += 8;
+        }
+        let value = self.bit_buf & ((1u32 << n) - 1);
+        self.bit_buf >>= n;
+        self.bit_count -= n;
+        Ok(value)
+    }
+
+    /// 残りのビットを捨ててバイト境界に揃える
+    fn align(&mut self) {
+        self.bit_buf = 0;
+        self.bit_count = 0;
+    }
+
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], PinkError> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|&end| end <= self.data.len())
+            .ok_or(PinkError::CorruptImageData)?;
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+}
+
+/// カノニカルハフマン符号表
+struct Huffman {
+    /// 符号長ごとの符号数
+    counts: [u16; MAX_BITS + 1],
+    /// 符号順に並べたシンボル
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Result<Self, PinkError> {
+        let mut counts = [0u16; MAX_BITS + 1];
+        for &len in lengths {
+            counts[len as usize] += 1;
+        }
+
+        // 符号が多すぎないか確認（不完全な符号は許容）
+        let mut left = 1i32;
+        for &count in &counts[1..] {
+            left = (left << 1) - count as i32;
+            if left < 0 {
+                return Err(PinkError::CorruptImageData);
+            }
+        }
+
+        let mut offsets = [0u16; MAX_BITS + 2];
+        for len in 1..=MAX_BITS {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0u16; lengths.len()];
+        for (symbol, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbols[offsets[len as usize] as usize] = symbol as u16;
+                offsets[len as usize] += 1;
+            }
+        }
+
+        Ok(Huffman { counts, symbols })
+    }
+
+    fn decode(&self, reader: &mut BitReader<'_>) -> Result<u16, PinkError> {
+        let mut code = 0i32;
+        let mut first = 0i32;
+        let mut index = 0i32;
+        for len in 1..=MAX_BITS {
+            code |= reader.bits(1)? as i32;
+            let count = self.counts[len] as i32;
+            if code - count < first {
+                return Ok(self.symbols[(index + code - first) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(PinkError::CorruptImageData)
+    }
+}
+
+/// zlibストリームを展開する（出力が `max_len` を超えればエラー）
+pub(crate) fn zlib_decompress(data: &[u8], max_len: usize) -> Result<Vec<u8>, PinkError> {
+    if data.len() < 6 {
+        return Err(PinkError::CorruptImageData);
+    }
+    let (cmf, flg) = (data[0], data[1]);
+    // CM = 8 (deflate)、ヘッダチェック、プリセット辞書なし
+    if cmf & 0x0F != 8 || !((cmf as u16) << 8 | flg as u16).is_multiple_of(31) || flg & 0x20 != 0 {
+        return Err(PinkError::CorruptImageData);
+    }
+
+    let mut reader = BitReader::new(&data[2..]);
+    let out = inflate(&mut reader, max_len)?;
+
+    let checksum = reader.bytes(4)?;
+    if adler32(&out).to_be_bytes() != checksum {
+        return Err(PinkError::CorruptImageData);
+    }
+    Ok(out)
+}
+
+fn inflate(reader: &mut BitReader<'_>, max_len: usize) -> Result<Vec<u8>, PinkError> {
+    let mut out = Vec::new();
+    loop {
+        let is_last = reader.bits(1)? == 1;
+        match reader.bits(2)? {
+            0 => inflate_stored(reader, &mut out, max_len)?,
+            1 => {
+                let (lit, dist) = fixed_tables()?;
+                inflate_block(reader, &mut out, &lit, &dist, max_len)?;
+            }
+            2 => {
+                let (lit, dist) = dynamic_tables(reader)?;
+                inflate_block(reader, &mut out, &lit, &dist, max_len)?;
+            }
+            _ => return Err(PinkError::CorruptImageData),
+        }
+        if is_last {
+            reader.align();
+            return Ok(out);
+        }
+    }
+}
+
+fn inflate_stored(
+    reader: &mut BitReader<'_>,
+    out: &mut Vec<u8>,
+    max_len: usize,
+) -> Result<(), PinkError> {
+    reader.align();
+    let header = reader.bytes(4)?;
+    let len = u16::from_le_bytes([header[0], header[1]]);
+    let nlen = u16::from_le_bytes([header[2], header[3]]);
+    if len != !nlen || out.len() + len as usize > max_len {
+        return Err(PinkError::CorruptImageData);
+    }
+    out.extend_from_slice(reader.bytes(len as usize)?);
+    Ok(())
+}
+
+fn fixed_tables() -> Result<(Huffman, Huffman), PinkError> {
+    let mut lengths = [0u8; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    Ok((Huffman::new(&lengths)?, Huffman::new(&[5u8; 30])?))
+}
+
+fn dynamic_tables(reader: &mut BitReader<'_>) -> Result<(Huffman, Huffman), PinkError> {
+    let hlit = reader.bits(5)? as usize + 257;
+    let hdist = reader.bits(5)? as usize + 1;
+    let hclen = reader.bits(4)? as usize + 4;
+    if hlit > 286 || hdist > 30 {
+        return Err(PinkError::CorruptImageData);
+    }
+
+    let mut code_lengths = [0u8; 19];
+    for &index in &CODE_LENGTH_ORDER[..hclen] {
+        code_lengths[index] = reader.bits(3)? as u8;
+    }
+    let code_length_huffman = Huffman::new(&code_lengths)?;
+
+    let mut lengths = vec![0u8; hlit + hdist];
+    let mut i = 0;
+    while i < lengths.len() {
+        let symbol = code_length_huffman.decode(reader)?;
+        let (value, repeat) = match symbol {
+            0..=15 => (symbol as u8, 1),
+            16 => {
+                let prev = *lengths[..i].last().ok_or(PinkError::CorruptImageData)?;
+                (prev, 3 + reader.bits(2)? as usize)
+            }
+            17 => (0, 3 + reader.bits(3)? as usize),
+            _ => (0, 11 + reader.bits(7)? as usize),
+        };
+        if i + repeat > lengths.len() {
+            return Err(PinkError::CorruptImageData);
+        }
+        lengths[i..i + repeat].fill(value);
+        i += repeat;
+    }
+
+    // ブロック終端符号（256）が無ければ不正
+    if lengths[256] == 0 {
+        return Err(PinkError::CorruptImageData);
+    }
+
+    Ok((
+        Huffman::new(&lengths[..hlit])?,
+        Huffman::new(&lengths[hlit..])?,
+    ))
+}
+
+fn inflate_block(
+    reader: &mut BitReader<'_>,
+    out: &mut Vec<u8>,
+    lit: &Huffman,
+    dist: &Huffman,
+    max_len: usize,
+) -> Result<(), PinkError> {
+    loop {
+        let symbol = lit.decode(reader)? as usize;
+        if symbol < 256 {
+            if out.len() >= max_len {
+                return Err(PinkError::CorruptImageData);
+            }
+            out.push(symbol as u8);
+            continue;
+        }
+        if symbol == 256 {
+            return Ok(());
+        }
+
+        let index = symbol - 257;
+        if index >= LENGTH_BASE.len() {
+            return Err(PinkError::CorruptImageData);
+        }
+        let len = LENGTH_BASE[index] as usize + reader.bits(LENGTH_EXTRA[index] as u32)? as usize;
+
+        let dist_symbol = dist.decode(reader)? as usize;
+        if dist_symbol >= DIST_BASE.len() {
+            return Err(PinkError::CorruptImageData);
+        }
+        let distance =
+            DIST_BASE[dist_symbol] as usize + reader.bits(DIST_EXTRA[dist_symbol] as u32)? as usize;
+
+        if distance > out.len() || out.len() + len > max_len {
+            return Err(PinkError::CorruptImageData);
+        }
+        // 重なりがありうるので1バイトずつコピー
+        let start = out.len() - distance;
+        for k in 0..len {
+            out.push(out[start + k]);
+        }
+    }
+}
@@ -0,0 +1,408 @@
+//! 最小限のベースラインJPEGエンコーダ（4:4:4、標準ハフマン表）と終端検出
+
+use alloc::{vec, vec::Vec};
+
+use crate::error::PinkError;
+
+const SOI: u8 = 0xD8;
+const EOI: u8 = 0xD9;
+const SOS: u8 = 0xDA;
+
+/// JPEGのシグネチャ（SOIマーカーと次のマーカーの先頭）
+pub(crate) const JPEG_SIGNATURE: [u8; 3] = [0xFF, SOI, 0xFF];
+
+/// ジグザグ順 → 自然順のインデックス
+const ZIGZAG: [usize; 64] = [
+    0, 1, 8, 16, 9, 2, 3, 10, 17, 24, 32, 25, 18, 11, 4, 5, 12, 19, 26, 33, 40, 48, 41, 34, 27, 20,
+    13, 6, 7, 14, 21, 28, 35, 42, 49, 56, 57, 50, 43, 36, 29, 22, 15, 23, 30, 37, 44, 51, 58, 59,
+    52, 45, 38, 31, 39, 46, 53, 60, 61, 54, 47, 55, 62, 63,
+];
+
+/// 標準の輝度量子化テーブル（ITU-T T.81 K.1、自然順）
+const LUMA_QUANT: [u16; 64] = [
+    16, 11, 10, 16, 24, 40, 51, 61, 12, 12, 14, 19, 26, 58, 60, 55, 14, 13, 16, 24, 40, 57, 69, 56,
+    14, 17, 22, 29, 51, 87, 80, 62, 18, 22, 37, 56, 68, 109, 103, 77, 24, 35, 55, 64, 81, 104, 113,
+    92, 49, 64, 78, 87, 103, 121, 120, 101, 72, 92, 95, 98, 112, 100, 103, 99,
+];
+
+/// 標準の色差量子化テーブル（ITU-T T.81 K.1、自然順）
+const CHROMA_QUANT: [u16; 64] = [
+    17, 18, 24, 47, 99, 99, 99, 99, 18, 21, 26, 66, 99, 99, 99, 99, 24, 26, 56, 99, 99, 99, 99, 99,
+    47, 66, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99,
+    99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99, 99,
+];
+
+/// 標準ハフマン表（ITU-T T.81 K.3）: 符号長ごとの個数
+const DC_LUMA_BITS: [u8; 16] = [0, 1, 5, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0];
+const DC_CHROMA_BITS: [u8; 16] = [0, 3, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0];
+const DC_VALUES: [u8; 12] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+
+const AC_LUMA_BITS: [u8; 16] = [0, 2, 1, 3, 3, 2, 4, 3, 5, 5, 4, 4, 0, 0, 1, 0x7D];
+const AC_LUMA_VALUES: [u8; 162] = [
+    0x01, 0x02, 0x03, 0x00, 0x04, 0x11, 0x05, 0x12, 0x21, 0x31, 0x41, 0x06, 0x13, 0x51, 0x61, 0x07,
+    0x22, 0x71, 0x14, 0x32, 0x81, 0x91, 0xA1, 0x08, 0x23, 0x42, 0xB1, 0xC1, 0x15, 0x52, 0xD1, 0xF0,
+    0x24, 0x33, 0x62, 0x72, 0x82, 0x09, 0x0A, 0x16, 0x17, 0x18, 0x19, 0x1A, 0x25, 0x26, 0x27, 0x28,
+    0x29, 0x2A, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3A, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49,
+    0x4A, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5A, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69,
+    0x6A, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7A, 0x83, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89,
+    0x8A, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9A, 0xA2, 0xA3, 0xA4, 0xA5, 0xA6, 0xA7,
+    0xA8, 0xA9, 0xAA, 0xB2, 0xB3, 0xB4, 0xB5, 0xB6, 0xB7, 0xB8, 0xB9, 0xBA, 0xC2, 0xC3, 0xC4, 0xC5,
+    0xC6, 0xC7, 0xC8, 0xC9, 0xCA, 0xD2, 0xD3, 0xD4, 0xD5, 0xD6, 0xD7, 0xD8, 0xD9, 0xDA, 0xE1, 0xE2,
+    0xE3, 0xE4, 0xE5, 0xE6, 0xE7, 0xE8, 0xE9, 0xEA, 0xF1, 0xF2, 0xF3, 0xF4, 0xF5, 0xF6, 0xF7, 0xF8,
+    0xF9, 0xFA,
+];
+
+const AC_CHROMA_BITS: [u8; 16] = [0, 2, 1, 2, 4, 4, 3, 4, 7, 5, 4, 4, 0, 1, 2, 0x77];
+const AC_CHROMA_VALUES: [u8; 162] = [
+    0x00, 0x01, 0x02, 0x03, 0x11, 0x04, 0x05, 0x21, 0x31, 0x06, 0x12, 0x41, 0x51, 0x07, 0x61, 0x71,
+    0x13, 0x22, 0x32, 0x81, 0x08, 0x14, 0x42, 0x91, 0xA1, 0xB1, 0xC1, 0x09, 0x23, 0x33, 0x52, 0xF0,
+    0x15, 0x62, 0x72, 0xD1, 0x0A, 0x16, 0x24, 0x34, 0xE1, 0x25, 0xF1, 0x17, 0x18, 0x19, 0x1A, 0x26,
+    0x27, 0x28, 0x29, 0x2A, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3A, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48,
+    0x49, 0x4A, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5A, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68,
+    0x69, 0x6A, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79, 0x7A, 0x82, 0x83, 0x84, 0x85, 0x86, 0x87,
+    0x88, 0x89, 0x8A, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98, 0x99, 0x9A, 0xA2, 0xA3, 0xA4, 0xA5,
+    0xA6, 0xA7, 0xA8, 0xA9, 0xAA, 0xB2, 0xB3, 0xB4, 0xB5, 0xB6, 0xB7, 0xB8, 0xB9, 0xBA, 0xC2, 0xC3,
+    0xC4, 0xC5, 0xC6, 0xC7, 0xC8, 0xC9, 0xCA, 0xD2, 0xD3, 0xD4, 0xD5, 0xD6, 0xD7, 0xD8, 0xD9, 0xDA,
+    0xE2, 0xE3, 0xE4, 0xE5, 0xE6, 0xE7, 0xE8, 0xE9, 0xEA, 0xF2, 0xF3, 0xF4, 0xF5, 0xF6, 0xF7, 0xF8,
+    0xF9, 0xFA,
+];
+
+/// DCT係数表: `C(u) / 2 * cos((2x + 1)uπ / 16)` を 2^12 倍して丸めたもの
+///
+/// 浮動小数点を使わないので、どの環境でも同じJPEGになる。
+const DCT: [[i64; 8]; 8] = [
+    [1448, 1448, 1448, 1448, 1448, 1448, 1448, 1448],
+    [2009, 1703, 1138, 400, -400, -1138, -1703, -2009],
+    [1892, 784, -784, -1892, -1892, -784, 784, 1892],
+    [1703, -400, -2009, -1138, 1138, 2009, 400, -1703],
+    [1448, -1448, -1448, 1448, 1448, -1448, -1448, 1448],
+    [1138, -2009, 400, 1703, -1703, -400, 2009, -1138],
+    [784, -1892, 1892, -784, -784, 1892, -1892, 784],
+    [400, -1138, 1703, -2009, 2009, -1703, 1138, -400],
+];
+
+/// 既定の品質（1〜100）
+pub const JPEG_DEFAULT_QUALITY: u8 = 90;
+
+/// 任意サイズのRGBAをベースラインJPEGにエンコード
+///
+/// JPEGはアルファを持たないため、アルファチャンネルは無視する。
+/// 幅・高さは 1〜65535（範囲外は `InvalidCover`）。`quality` は 1〜100 に丸める。
+pub fn encode_jpeg_rgba(
+    rgba: &[u8],
+    width: usize,
+    height: usize,
+    quality: u8,
+) -> Result<Vec<u8>, PinkError> {
+    if width == 0 || height == 0 || width > 0xFFFF || height > 0xFFFF {
+        return Err(PinkError::InvalidCover);
+    }
+    debug_assert_eq!(rgba.len(), width * height * 4);
+
+    let luma_quant = scale_quant(&LUMA_QUANT, quality);
+    let chroma_quant = scale_quant(&CHROMA_QUANT, quality);
+    let dc_luma = HuffmanTable::new(&DC_LUMA_BITS, &DC_VALUES);
+    let dc_chroma = HuffmanTable::new(&DC_CHROMA_BITS, &DC_VALUES);
+    let ac_luma = HuffmanTable::new(&AC_LUMA_BITS, &AC_LUMA_VALUES);
+    let ac_chroma = HuffmanTable::new(&AC_CHROMA_BITS, &AC_CHROMA_VALUES);
+
+    let mut out = vec![0xFF, SOI];
+
+    // APP0 (JFIF 1.01、アスペクト比 1:1)
+    write_segment(
+        &mut out,
+        0xE0,
+        &[b'J', b'F', b'I', b'F', 0, 1, 1, 0, 0, 1, 0, 1, 0, 0],
+    );
+
+    // DQT（テーブル0: 輝度、1: 色差）
+    let mut dqt = Vec::with_capacity(130);
+    for (id, table) in [&luma_quant, &chroma_quant].into_iter().enumerate() {
+        dqt.push(id as u8);
+        dqt.extend(ZIGZAG.iter().map(|&i| table[i] as u8));
+    }
+    write_segment(&mut out, 0xDB, &dqt);
+
+    // SOF0: 8bit、3成分、サンプリング 1x1（4:4:4）
+    let mut sof = vec![8];
+    sof.extend_from_slice(&(height as u16).to_be_bytes());
+    sof.extend_from_slice(&(width as u16).to_be_bytes());
+    sof.push(3);
+    sof.extend_from_slice(&[1, 0x11, 0, 2, 0x11, 1, 3, 0x11, 1]);
+    write_segment(&mut out, 0xC0, &sof);
+
+    // DHT（クラス0: DC、1: AC）
+    let mut dht = Vec::new();
+    for (class_id, bits, values) in [
+        (0x00, &DC_LUMA_BITS, &DC_VALUES[..]),
+        (0x10, &AC_LUMA_BITS, &AC_LUMA_VALUES[..]),
+        (0x01, &DC_CHROMA_BITS, &DC_VALUES[..]),
+        (0x11, &AC_CHROMA_BITS, &AC_CHROMA_VALUES[..]),
+    ] {
+        dht.push(class_id);
+        dht.extend_from_slice(bits);
+        dht.extend_from_slice(values);
+    }
+    write_segment(&mut out, 0xC4, &dht);
+
+    // SOS: 成分ごとのハフマン表（Y: 0/0、Cb・Cr: 1/1）、スペクトル選択 0〜63
+    write_segment(&mut out, SOS, &[3, 1, 0x00, 2, 0x11, 3, 0x11, 0, 63, 0]);
+
+    let mut writer = BitWriter::new(&mut out);
+    let mut prev_dc = [0i32; 3];
+    let mut block = [[0i32; 64]; 3];
+    for block_y in (0..height).step_by(8) {
+        for block_x in (0..width).step_by(8) {
+            // 端のブロックは最後の画素を繰り返して埋める
+            for row in 0..8 {
+                let y = (block_y + row).min(height - 1);
+                for col in 0..8 {
+                    let x = (block_x + col).min(width - 1);
+                    let base = (y * width + x) * 4;
+                    let (luma, cb, cr) = rgb_to_ycbcr(rgba[base], rgba[base + 1], rgba[base + 2]);
+                    block[0][row * 8 + col] = luma - 128;
+                    block[1][row * 8 + col] = cb - 128;
+                    block[2][row * 8 + col] = cr - 128;
+                }
+            }
+
+            for (component, samples) in block.iter().enumerate() {
+                let (quant, dc, ac) = if component == 0 {
+                    (&luma_quant, &dc_luma, &ac_luma)
+                } else {
+                    (&chroma_quant, &dc_chroma, &ac_chroma)
+                };
+                let coefficients = forward_dct(samples, quant);
+                encode_block(&mut writer, &coefficients, &mut prev_dc[component], dc, ac);
+            }
+        }
+    }
+    writer.flush();
+
+    out.extend_from_slice(&[0xFF, EOI]);
+    Ok(out)
+}
+
+/// JFIFの整数近似によるYCbCr変換
+fn rgb_to_ycbcr(r: u8, g: u8, b: u8) -> (i32, i32, i32) {
+    let (r, g, b) = (r as i32, g as i32, b as i32);
+    let luma = (77 * r + 150 * g + 29 * b + 128) >> 8;
+    let cb = ((-43 * r - 85 * g + 128 * b + 128) >> 8) + 128;
+    let cr = ((128 * r - 107 * g - 21 * b + 128) >> 8) + 128;
+    (luma, cb.clamp(0, 255), cr.clamp(0, 255))
+}
+
+/// libjpeg と同じ方式で品質に応じて量子化テーブルをスケーリング
+fn scale_quant(base: &[u16; 64], quality: u8) -> [u16; 64] {
+    let quality = quality.clamp(1, 100) as u32;
+    let scale = if quality < 50 {
+        5000 / quality
+    } else {
+        200 - quality * 2
+    };
+    base.map(|q| ((q as u32 * scale + 50) / 100).clamp(1, 255) as u16)
+}
+
+/// 8x8ブロックをDCTして量子化し、ジグザグ順で返す
+fn forward_dct(samples: &[i32; 64], quant: &[u16; 64]) -> [i32; 64] {
+    // 行方向（2^12 倍）
+    let mut rows = [0i64; 64];
+    for y in 0..8 {
+        for u in 0..8 {
+            rows[y * 8 + u] = (0..8).map(|x| samples[y * 8 + x] as i64 * DCT[u][x]).sum();
+        }
+    }
+
+    let mut out = [0i32; 64];
+    for (index, &natural) in ZIGZAG.iter().enumerate() {
+        let (v, u) = (natural / 8, natural % 8);
+        // 列方向（2^24 倍）→ 量子化まで一度に丸める
+        let sum: i64 = (0..8).map(|y| rows[y * 8 + u] * DCT[v][y]).sum();
+        let divisor = (quant[natural] as i64) << 24;
+        let rounded = if sum >= 0 {
+            (sum + divisor / 2) / divisor
+        } else {
+            -((-sum + divisor / 2) / divisor)
+        };
+        out[index] = rounded as i32;
+    }
+    out
+}
+
+/// 1ブロック分の係数をハフマン符号化
+fn encode_block(
+    writer: &mut BitWriter<'_>,
+    coefficients: &[i32; 64],
+    prev_dc: &mut i32,
+    dc: &HuffmanTable,
+    ac: &HuffmanTable,
+) {
+    let diff = coefficients[0] - *prev_dc;
+    *prev_dc = coefficients[0];
+    let (size, bits) = magnitude(diff);
+    dc.write(writer, size);
+    writer.write(bits, size);
+
+    let mut run = 0u8;
+    for &coefficient in &coefficients[1..] {
+        if coefficient == 0 {
+            run += 1;
+            continue;
+        }
+        while run >= 16 {
+            ac.write(writer, 0xF0); // ZRL
+            run -= 16;
+        }
+        let (size, bits) = magnitude(coefficient);
+        ac.write(writer, (run << 4) | size);
+        writer.write(bits, size);
+        run = 0;
+    }
+    if run > 0 {
+        ac.write(writer, 0x00); // EOB
+    }
+}
+
+/// 係数の大きさのカテゴリと付加ビット
+fn magnitude(value: i32) -> (u8, u16) {
+    let size = (32 - value.unsigned_abs().leading_zeros()) as u8;
+    let bits = if value < 0 {
+        value - 1 + (1 << size)
+    } else {
+        value
+    };
+    (size, bits as u16)
+}
+
+/// 符号化用のハフマン表（シンボル → (符号, 符号長)）
+struct HuffmanTable {
+    codes: [(u16, u8); 256],
+}
+
+impl HuffmanTable {
+    fn new(bits: &[u8; 16], values: &[u8]) -> Self {
+        let mut codes = [(0u16, 0u8); 256];
+        let mut code = 0u16;
+        let mut values = values.iter();
+        for (index, &count) in bits.iter().enumerate() {
+            for _ in 0..count {
+                let symbol = *values.next().expect("huffman table values");
+                codes[symbol as usize] = (code, index as u8 + 1);
+                code += 1;
+            }
+            code <<= 1;
+        }
+        HuffmanTable { codes }
+    }
+
+    fn write(&self, writer: &mut BitWriter<'_>, symbol: u8) {
+        let (code, len) = self.codes[symbol as usize];
+        writer.write(code, len);
+    }
+}
+
+/// MSBから詰めるビットライタ（0xFF の後に 0x00 を挿入する）
+struct BitWriter<'a> {
+    out: &'a mut Vec<u8>,
+    acc: u32,
+    count: u8,
+}
+
+impl<'a> BitWriter<'a> {
+    fn new(out: &'a mut Vec<u8>) -> Self {
+        BitWriter {
+            out,
+            acc: 0,
+            count: 0,
+        }
+    }
+
+    fn write(&mut self, bits: u16, len: u8) {
+        for i in (0..len).rev() {
+            self.acc = (self.acc << 1) | ((bits >> i) & 1) as u32;
+            self.count += 1;
+            if self.count == 8 {
+                self.emit();
+            }
+        }
+    }
+
+    fn emit(&mut self) {
+        let byte = self.acc as u8;
+        self.out.push(byte);
+        if byte == 0xFF {
+            self.out.push(0x00);
+        }
+        self.acc = 0;
+        self.count = 0;
+    }
+
+    /// 残りを1で埋めて書き出す
+    fn flush(&mut self) {
+        if self.count > 0 {
+            let pad = 8 - self.count;
+            self.acc = (self.acc << pad) | ((1 << pad) - 1);
+            self.emit();
+        }
+    }
+}
+
+/// マーカーセグメント（長さ付き）を書き込み
+fn write_segment(out: &mut Vec<u8>, marker: u8, data: &[u8]) {
+    out.extend_from_slice(&[0xFF, marker]);
+    out.extend_from_slice(&((data.len() + 2) as u16).to_be_bytes());
+    out.extend_from_slice(data);
+}
+
+/// JPEGの終端（EOIマーカーの直後）を探す
+///
+/// マーカーセグメントは長さで読み飛ばし、エントロピー符号化データ内は
+/// バイトスタッフィングとリスタートマーカーを考慮して走査する。
+/// EXIFサムネイル等のセグメント内のEOIには反応しない。
+pub fn find_jpeg_end(data: &[u8]) -> Result<usize, PinkError> {
+    if !data.starts_with(&[0xFF, SOI]) {
+        return Err(PinkError::InvalidFormat);
+    }
+
+    let mut pos = 2;
+    loop {
+        // フィルバイト（連続する 0xFF）を読み飛ばす
+        while data.get(pos) == Some(&0xFF) && data.get(pos + 1) == Some(&0xFF) {
+            pos += 1;
+        }
+        if data.get(pos) != Some(&0xFF) {
+            return Err(PinkError::MissingImageEnd);
+        }
+        let marker = *data.get(pos + 1).ok_or(PinkError::MissingImageEnd)?;
+        pos += 2;
+
+        match marker {
+            EOI => return Ok(pos),
+            // 長さを持たないマーカー（TEM、RSTn）
+            0x01 | 0xD0..=0xD7 => continue,
+            _ => {}
+        }
+
+        let len_bytes = data.get(pos..pos + 2).ok_or(PinkError::MissingImageEnd)?;
+        let len = u16::from_be_bytes([len_bytes[0], len_bytes[1]]) as usize;
+        if len < 2 {
+            return Err(PinkError::InvalidFormat);
+        }
+        pos += len;
+        if pos > data.len() {
+            return Err(PinkError::MissingImageEnd);
+        }
+
+        if marker == SOS {
+            // 次のマーカー（スタッフィング・RSTn以外）までがスキャンデータ
+            loop {
+                match data.get(pos..pos + 2) {
+                    Some([0xFF, 0x00]) | Some([0xFF, 0xD0..=0xD7]) => pos += 2,
+                    Some([0xFF, _]) => break,
+                    Some(_) => pos += 1,
+                    None => return Err(PinkError::MissingImageEnd),
+                }
+            }
+        }
+    }
+}
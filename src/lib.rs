@@ -1,29 +1,134 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+// アロケータの無い構成では、フレームを読む側の補助関数の多くが使われない
+#![cfg_attr(not(feature = "alloc"), allow(dead_code))]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "async")]
+mod async_file;
+#[cfg(feature = "alloc")]
+mod bmp;
+#[cfg(feature = "alloc")]
+mod carrier;
 mod constants;
 mod core;
 mod cover;
+#[cfg(feature = "alloc")]
+mod encrypt;
 mod error;
+#[cfg(feature = "std")]
 mod file;
+#[cfg(feature = "alloc")]
+mod gif;
+#[cfg(feature = "alloc")]
+mod inflate;
+#[cfg(feature = "alloc")]
+mod jpeg;
+#[cfg(feature = "alloc")]
+mod locate;
 mod noise;
+#[cfg(feature = "alloc")]
 mod png;
+#[cfg(feature = "std")]
+mod scan;
+mod seed;
+mod sign;
+#[cfg(feature = "alloc")]
+mod stream;
+#[cfg(feature = "alloc")]
+mod webp;
 
+#[cfg(feature = "ffi")]
+mod ffi;
+#[cfg(feature = "python")]
+mod python;
 #[cfg(feature = "wasm")]
 mod wasm;
 
+pub use ed25519_dalek::{SigningKey, VerifyingKey};
+pub use x25519_dalek::{PublicKey as RecipientKey, StaticSecret as RecipientSecret};
+
+#[cfg(feature = "async")]
+pub use async_file::{
+    decode_auto_async, decode_auto_async_with, encode_file_async, encode_file_async_with,
+    encode_folder_async, encode_folder_async_with, read_payload_async, write_pnk_async,
+};
+#[cfg(feature = "alloc")]
+pub use bmp::{encode_bmp_rgba, find_bmp_end};
+#[cfg(feature = "alloc")]
+pub use carrier::{
+    decode_carrier, encode_carrier, BmpCarrier, Carrier, CarrierFormat, GifCarrier, JpegCarrier,
+    PngCarrier, WebPCarrier,
+};
 pub use constants::*;
-pub use core::{pink072_unwrap, pink072_wrap, pink072_wrap_into};
+#[cfg(feature = "alloc")]
+pub use core::{pink072_unwrap, pink072_unwrap_strict, pink072_wrap, pink072_wrap_with_cover};
+pub use core::{pink072_wrap_into, FrameInfo};
+#[cfg(feature = "alloc")]
+pub use cover::{
+    render_cover_apng, render_cover_png, CoverStyle, COVER_APNG_MAX_FRAMES, COVER_RENDER_MAX_SIZE,
+};
+#[cfg(all(feature = "alloc", feature = "rand"))]
+pub use encrypt::pink072_wrap_encrypted;
+#[cfg(feature = "alloc")]
+pub use encrypt::{frame_recipients, pink072_unwrap_encrypted};
 pub use error::PinkError;
+#[cfg(feature = "std")]
 pub use file::{
-    decode_auto, decode_file, decode_folder, decode_raw, encode_auto, encode_file, encode_folder,
-    encode_raw, PAYLOAD_TYPE_FILE, PAYLOAD_TYPE_RAW, PAYLOAD_TYPE_ZIP,
+    build_zip_payload, decode_auto, decode_auto_with, decode_file, decode_file_with, decode_folder,
+    decode_folder_with, decode_raw, decode_raw_with, detect_pnk, encode_auto, encode_auto_with,
+    encode_file, encode_file_with, encode_folder, encode_folder_with, encode_raw, encode_raw_with,
+    list_zip_entries, read_zip_entry, DecodeOptions, EncodeOptions, ZipEntry, PAYLOAD_TYPE_FILE,
+    PAYLOAD_TYPE_RAW, PAYLOAD_TYPE_ZIP,
 };
-pub use png::{decode_pnk, encode_pnk};
+#[cfg(feature = "alloc")]
+pub use gif::{encode_gif_rgba, find_gif_end};
+#[cfg(feature = "alloc")]
+pub use jpeg::{encode_jpeg_rgba, find_jpeg_end, JPEG_DEFAULT_QUALITY};
+#[cfg(feature = "alloc")]
+pub use locate::{find_pnk_frame, FindOptions, FoundFrame, FrameSource};
+#[cfg(feature = "alloc")]
+pub use png::{
+    decode_png, decode_pnk, decode_pnk_image, decode_pnk_strict, encode_apng_rgba, encode_png_rgba,
+    encode_pnk, encode_pnk_animated, encode_pnk_with_layout, find_png_end, inspect_pnk, is_pnk,
+    pnk_info, DecodedImage, PngChunk, PngChunkInfo, PngChunks, PnkInfo, PnkInspection, PnkLayout,
+    PNK_CHUNK_MAX_LEN, PNK_CHUNK_TYPE,
+};
+#[cfg(feature = "std")]
+pub use scan::{scan, ScanResult};
+pub use seed::Seed9;
+pub use sign::{frame_key_id, KeyId};
+#[cfg(feature = "alloc")]
+pub use sign::{sign_frame, verify_frame, Keyring};
+#[cfg(feature = "alloc")]
+pub use stream::{PnkDecoder, PnkEvent};
+#[cfg(feature = "alloc")]
+pub use webp::{encode_webp_rgba, find_webp_end};
 
 #[cfg(feature = "wasm")]
 pub use wasm::{wasm_pink072_unwrap, wasm_pink072_wrap, wasm_pink072_wrap_into};
 
-#[cfg(test)]
+/// ファジング用に内部のパーサを公開する（`fuzz/` から使う。安定したAPIではない）
+#[cfg(feature = "fuzzing")]
+#[doc(hidden)]
+pub mod fuzzing {
+    use std::io;
+    use std::path::Path;
+
+    pub fn parse_file_payload(payload: &[u8]) -> io::Result<(String, &[u8])> {
+        crate::file::parse_file_payload(payload)
+    }
+
+    pub fn extract_zip_to_folder(zip_data: &[u8], output_dir: &Path) -> io::Result<Vec<String>> {
+        crate::file::extract_zip_to_folder(zip_data, output_dir)
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
     use std::fs;
     use std::path::Path;
 
@@ -34,7 +139,7 @@ mod tests {
     #[test]
     fn round_trip_preserves_payload_and_type() {
         let payload = b"hello pink072";
-        let frame = pink072_wrap(payload, 2, &seed()).expect("wrap");
+        let frame = pink072_wrap(payload, 2, seed()).expect("wrap");
         let (ptype, out) = pink072_unwrap(&frame).expect("unwrap");
         assert_eq!(ptype, 2);
         assert_eq!(out, payload);
@@ -45,28 +150,143 @@ mod tests {
         let payload = vec![0xAA; 10];
         let total = HEADER_LEN + COVER_LEN + payload.len();
         let mut buf = vec![0u8; total];
-        let written = pink072_wrap_into(&payload, 4, &seed(), &mut buf).expect("wrap_into");
+        let written = pink072_wrap_into(&payload, 4, seed(), &mut buf).expect("wrap_into");
         assert_eq!(written, total);
-        assert_eq!(buf[0], 1);
-        assert_eq!(buf[1], 4);
-        assert_eq!(buf[2], BLOCK_SIZE as u8);
+        assert_eq!(&buf[0..4], b"PINK");
+        assert_eq!(buf[4], FRAME_VERSION);
+        assert_eq!(buf[5], 4);
+        assert_eq!(buf[6], BLOCK_SIZE as u8);
         assert_eq!(buf.len(), HEADER_LEN + COVER_LEN + payload.len());
     }
 
     #[test]
     fn invalid_seed_errors() {
-        let payload = [0u8; 1];
-        let mut out = vec![0u8; HEADER_LEN + COVER_LEN + payload.len()];
-        let err =
-            pink072_wrap_into(&payload, 0, &[0xAA], &mut out).expect_err("expected seed error");
+        let err = Seed9::try_from(&[0xAA][..]).expect_err("expected seed error");
         assert_eq!(err, PinkError::SeedLength);
+        assert_eq!(Seed9::try_from(&seed()[..]), Ok(Seed9::new(seed())));
+    }
+
+    #[test]
+    fn seed_text_encodings_round_trip() {
+        let seed9 = Seed9::new(seed());
+        assert_eq!(seed9.to_string(), "123456789abcdef011");
+        assert_eq!(seed9.to_base32(), "CI2FM6E2XTPPAEI");
+        assert_eq!(
+            seed9.to_words(),
+            "barn-cedar-dew-gecko-lark-palm-sage-sugar-banjo"
+        );
+
+        for text in [
+            "123456789abcdef011",
+            "123456789ABCDEF011",
+            "CI2FM6E2XTPPAEI",
+            "ci2fm6e2xtppaei",
+            "barn-cedar-dew-gecko-lark-palm-sage-sugar-banjo",
+            "Barn Cedar Dew Gecko Lark Palm Sage Sugar Banjo",
+        ] {
+            assert_eq!(text.parse::<Seed9>(), Ok(seed9), "{text}");
+        }
+
+        for text in [
+            "",
+            "1234",
+            "CI2FM6E2XTPPAE7",
+            "barn-cedar",
+            "not a seed at all",
+        ] {
+            assert_eq!(text.parse::<Seed9>(), Err(PinkError::InvalidSeed), "{text}");
+        }
+    }
+
+    #[test]
+    fn seed_from_passphrase_is_deterministic() {
+        let a = Seed9::from_passphrase("correct horse battery staple");
+        let b = Seed9::from_passphrase("correct horse battery staple");
+        let c = Seed9::from_passphrase("correct horse battery stable");
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(format!("{a:?}"), "Seed9(..)");
+
+        let random = Seed9::random().expect("random");
+        let frame = pink072_wrap(b"r", 0, random).expect("wrap");
+        assert!(pink072_unwrap_strict(&frame, random).is_ok());
+    }
+
+    /// 固定小数点ノイズのゴールデンベクタ（カバー全体のCRC32と先頭2ピクセル）
+    #[test]
+    fn cover_golden_vectors() {
+        let vectors: [([u8; 9], u32, [u8; 8]); 4] = [
+            (
+                seed(),
+                0xa6b1_bbba,
+                [0xff, 0xc2, 0xcd, 0xff, 0xff, 0xc2, 0xce, 0xff],
+            ),
+            (
+                [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09],
+                0x5acc_2187,
+                [0xff, 0xce, 0xd7, 0xff, 0xff, 0xce, 0xd7, 0xff],
+            ),
+            (
+                [0xFF; 9],
+                0x3e09_b098,
+                [0xff, 0xc4, 0xcf, 0xff, 0xff, 0xc4, 0xcf, 0xff],
+            ),
+            (
+                [0xA5, 0x5A, 0x00, 0xC3, 0x3C, 0x99, 0x66, 0x81, 0x7E],
+                0x7693_205d,
+                [0xff, 0xc2, 0xce, 0xff, 0xff, 0xc2, 0xcd, 0xff],
+            ),
+        ];
+
+        for (seed9, expected_crc, expected_head) in vectors {
+            let frame = pink072_wrap(&[], 0, seed9).expect("wrap");
+            let cover = &frame[HEADER_LEN..HEADER_LEN + COVER_LEN];
+            assert_eq!(crate::png::crc32(cover), expected_crc, "seed {seed9:02X?}");
+            assert_eq!(&cover[..8], &expected_head, "seed {seed9:02X?}");
+        }
+    }
+
+    #[test]
+    fn render_cover_png_scales_cover() {
+        let png = render_cover_png(seed(), 72, CoverStyle::Square).expect("render");
+        let frame = pink072_wrap(&[], 0, seed()).expect("wrap");
+        assert_eq!(
+            png,
+            encode_png_rgba(&frame[HEADER_LEN..HEADER_LEN + COVER_LEN], 72, 72)
+        );
+
+        let big = render_cover_png(seed(), 256, CoverStyle::Circle).expect("render");
+        assert_eq!(&big[16..24], &[0, 0, 1, 0, 0, 0, 1, 0]); // 256x256
+        assert_eq!(
+            render_cover_png(seed(), 0, CoverStyle::Square),
+            Err(PinkError::InvalidCover)
+        );
+
+        let max = render_cover_png(seed(), COVER_RENDER_MAX_SIZE, CoverStyle::Square)
+            .expect("render at the maximum size");
+        assert_eq!(&max[16..24], &[0, 0, 0x10, 0, 0, 0, 0x10, 0]); // 4096x4096
+        assert_eq!(
+            render_cover_png(seed(), COVER_RENDER_MAX_SIZE + 1, CoverStyle::Square),
+            Err(PinkError::InvalidCover)
+        );
+        assert_eq!(
+            render_cover_png(seed(), u32::MAX, CoverStyle::Circle),
+            Err(PinkError::InvalidCover)
+        );
+    }
+
+    #[test]
+    fn seed_fingerprint_is_stable_and_short() {
+        let fp = Seed9::new(seed()).fingerprint();
+        assert_eq!(fp, "SYWU-IFKL");
+        assert_ne!(fp, Seed9::new([0; 9]).fingerprint());
     }
 
     #[test]
     fn pnk_encode_decode_round_trip() {
         let payload = b"test pnk format";
-        let frame = pink072_wrap(payload, 0, &seed()).expect("wrap");
-        let pnk = encode_pnk(&frame);
+        let frame = pink072_wrap(payload, 0, seed()).expect("wrap");
+        let pnk = encode_pnk(&frame).expect("encode_pnk");
 
         // PNKはPNGシグネチャで始まる
         assert_eq!(
@@ -76,9 +296,792 @@ mod tests {
 
         // デコード
         let decoded_frame = decode_pnk(&pnk).expect("decode_pnk");
-        let (ptype, out) = pink072_unwrap(decoded_frame).expect("unwrap");
+        let (ptype, out) = pink072_unwrap(&decoded_frame).expect("unwrap");
         assert_eq!(ptype, 0);
         assert_eq!(out, payload);
+
+        // 不正なフレームはパニックせずエラーになる
+        assert_eq!(encode_pnk(&frame[..10]), Err(PinkError::FrameTooSmall));
+        assert_eq!(
+            encode_pnk_with_layout(&frame[..HEADER_LEN + 1], PnkLayout::Chunk),
+            Err(PinkError::FrameTooSmall)
+        );
+        let mut unknown = frame.clone();
+        unknown[4] = 9;
+        assert_eq!(
+            encode_pnk(&unknown),
+            Err(PinkError::UnsupportedVersion { version: 9 })
+        );
+    }
+
+    #[test]
+    fn strict_decode_accepts_matching_seed() {
+        let frame = pink072_wrap(b"strict", 1, seed()).expect("wrap");
+        let (ptype, out) = pink072_unwrap_strict(&frame, seed()).expect("unwrap_strict");
+        assert_eq!(ptype, 1);
+        assert_eq!(out, b"strict");
+
+        let pnk = encode_pnk(&frame).expect("encode_pnk");
+        let decoded = decode_pnk_strict(&pnk, seed()).expect("decode_pnk_strict");
+        assert_eq!(decoded, frame.as_slice());
+    }
+
+    #[test]
+    fn strict_decode_rejects_other_seed() {
+        let other = [0u8; 9];
+        let frame = pink072_wrap(b"strict", 1, seed()).expect("wrap");
+        assert_eq!(
+            pink072_unwrap_strict(&frame, other),
+            Err(PinkError::CoverMismatch)
+        );
+        assert_eq!(
+            decode_pnk_strict(&encode_pnk(&frame).expect("encode_pnk"), other),
+            Err(PinkError::CoverMismatch)
+        );
+
+        // フレームのカバーは正しくても、表示されるPNGが差し替えられていれば拒否
+        let mut forged =
+            encode_pnk(&pink072_wrap(b"x", 0, other).expect("wrap")).expect("encode_pnk");
+        let png_len = forged.len() - (HEADER_LEN + COVER_LEN + 1);
+        forged.truncate(png_len);
+        forged.extend_from_slice(&frame);
+        assert_eq!(
+            decode_pnk_strict(&forged, seed()),
+            Err(PinkError::CoverMismatch)
+        );
+    }
+
+    #[test]
+    fn external_cover_round_trip() {
+        let (width, height) = (5u32, 3u32);
+        let rgba: Vec<u8> = (0..width * height * 4).map(|i| i as u8).collect();
+        let frame =
+            pink072_wrap_with_cover(b"logo", 1, &rgba, width, height).expect("wrap_with_cover");
+        assert_eq!(frame[7] & FLAG_EXTERNAL_COVER, FLAG_EXTERNAL_COVER);
+        assert_eq!(frame.len(), HEADER_LEN + rgba.len() + 4);
+
+        let pnk = encode_pnk(&frame).expect("encode_pnk");
+        assert_eq!(&pnk[16..24], &[0, 0, 0, 5, 0, 0, 0, 3]); // IHDRの幅・高さ
+        assert_eq!(
+            pnk[..pnk.len() - frame.len()],
+            encode_png_rgba(&rgba, 5, 3)[..]
+        );
+
+        let decoded = decode_pnk(&pnk).expect("decode_pnk");
+        let (ptype, out) = pink072_unwrap(&decoded).expect("unwrap");
+        assert_eq!(ptype, 1);
+        assert_eq!(out, b"logo");
+
+        // 外部カバーはシードから再生成できないので厳格モードでは拒否
+        assert_eq!(
+            decode_pnk_strict(&pnk, seed()),
+            Err(PinkError::CoverMismatch)
+        );
+    }
+
+    #[test]
+    fn external_cover_rejects_mismatched_size() {
+        assert_eq!(
+            pink072_wrap_with_cover(b"", 0, &[0u8; 12], 2, 2),
+            Err(PinkError::InvalidCover)
+        );
+        assert_eq!(
+            pink072_wrap_with_cover(b"", 0, &[], 0, 0),
+            Err(PinkError::InvalidCover)
+        );
+    }
+
+    #[test]
+    fn animated_pnk_round_trip() {
+        let frame = pink072_wrap(b"sticker", 0, seed()).expect("wrap");
+        let pnk = encode_pnk_animated(&frame, seed(), 12, 80).expect("encode_pnk_animated");
+
+        let count = |chunk: &[u8]| pnk.windows(4).filter(|w| *w == chunk).count();
+        assert_eq!(count(b"acTL"), 1);
+        assert_eq!(count(b"fcTL"), 12);
+        assert_eq!(count(b"fdAT"), 11);
+
+        let decoded = decode_pnk(&pnk).expect("decode_pnk");
+        assert_eq!(decoded, frame.as_slice());
+        let (_, out) = pink072_unwrap(&decoded).expect("unwrap");
+        assert_eq!(out, b"sticker");
+
+        assert_eq!(
+            encode_pnk_animated(&frame, [0u8; 9], 12, 80),
+            Err(PinkError::CoverMismatch)
+        );
+        assert_eq!(
+            render_cover_apng(seed(), 0, 80),
+            Err(PinkError::InvalidCover)
+        );
+        assert_eq!(
+            render_cover_apng(seed(), COVER_APNG_MAX_FRAMES + 1, 80),
+            Err(PinkError::InvalidCover)
+        );
+        assert_eq!(
+            encode_pnk_animated(&frame, seed(), COVER_APNG_MAX_FRAMES + 1, 80),
+            Err(PinkError::InvalidCover)
+        );
+    }
+
+    #[test]
+    fn png_chunks_are_validated() {
+        let frame = pink072_wrap(b"chunks", 0, seed()).expect("wrap");
+        let pnk = encode_pnk(&frame).expect("encode_pnk");
+        let types: Vec<[u8; 4]> = PngChunks::new(&pnk)
+            .expect("signature")
+            .map(|c| c.expect("chunk").chunk_type)
+            .collect();
+        assert_eq!(types, [*b"IHDR", *b"IDAT", *b"IEND"]);
+        let png_end = find_png_end(&pnk).expect("find_png_end");
+        assert_eq!(png_end, pnk.len() - frame.len());
+
+        // IDATのデータを1バイト壊すとCRCエラー
+        let mut corrupt = pnk.clone();
+        corrupt[33 + 8 + 10] ^= 0xFF;
+        assert_eq!(
+            decode_pnk(&corrupt),
+            Err(PinkError::PngCrcMismatch { offset: 33 })
+        );
+
+        // 途中で切れたPNG
+        assert_eq!(
+            find_png_end(&pnk[..png_end - 12]),
+            Err(PinkError::PngMissingIend)
+        );
+
+        // チャンク長がデータを越える
+        let mut overflow = pnk.clone();
+        overflow[33..37].copy_from_slice(&u32::MAX.to_be_bytes());
+        assert_eq!(
+            find_png_end(&overflow),
+            Err(PinkError::PngChunkOverflow { offset: 33 })
+        );
+
+        // IHDRとIDATを入れ替える
+        let mut reordered = pnk[..8].to_vec();
+        reordered.extend_from_slice(&pnk[33..png_end - 12]);
+        reordered.extend_from_slice(&pnk[8..33]);
+        reordered.extend_from_slice(&pnk[png_end - 12..]);
+        assert_eq!(find_png_end(&reordered), Err(PinkError::PngMissingIhdr));
+    }
+
+    #[test]
+    fn inspect_pnk_reports_structure() {
+        let frame = pink072_wrap(b"inspect", 2, seed()).expect("wrap");
+        let pnk = encode_pnk(&frame).expect("encode_pnk");
+        let inspection = inspect_pnk(&pnk);
+        assert_eq!(inspection.png_error, None);
+        assert_eq!(inspection.chunks.len(), 3);
+        assert_eq!(inspection.chunks[0].offset, 8);
+        assert_eq!(inspection.chunks[0].length, 13);
+        assert_eq!(inspection.trailing_len, frame.len());
+        let info = inspection.frame.expect("frame").expect("header");
+        assert_eq!(info.payload_type, 2);
+        assert_eq!(info.payload_len, 7);
+        assert_eq!((info.cover_width, info.cover_height), (72, 72));
+
+        let broken = inspect_pnk(&pnk[..40]);
+        assert_eq!(broken.chunks.len(), 1);
+        assert_eq!(broken.png_error, Some(PinkError::PngMissingIend));
+        assert_eq!(broken.frame, None);
+    }
+
+    /// 空白を無視して16進文字列をバイト列に変換
+    fn hex_bytes(s: &str) -> Vec<u8> {
+        let digits: Vec<u8> = s.bytes().filter(|b| !b.is_ascii_whitespace()).collect();
+        digits
+            .chunks(2)
+            .map(|pair| u8::from_str_radix(std::str::from_utf8(pair).unwrap(), 16).unwrap())
+            .collect()
+    }
+
+    /// 外部ツール（zlib level 9、行ごとにフィルタ0〜4を循環）で作った7x5 RGB画像
+    const PNG_RGB_FIXED_HUFFMAN: &str = "
+        89504e470d0a1a0a0000000d494844520000000700000005080200000006f8618f0000000c74455874436f6d6d656e74
+        007465737457612be90000006d4944415478da63606060506594f562b1cae70c9f2250b25372e23d95758cdca6b2aa8c
+        8f5599c355591fabb287ab723e56e50e67028a729b86739baee4367d0c67338b655949483f96906190907d2c21572221
+        ff584281810524cf68c5cd2ccbcd2acbcd6ecacd29cbcd2d0b0067ee16780fd16d130000000049454e44ae426082
+    ";
+
+    /// 同じ方法で作った24x24 RGBA画像（動的ハフマン符号）
+    const PNG_RGBA_DYNAMIC_HUFFMAN: &str = "
+        89504e470d0a1a0a0000000d4948445200000018000000180806000000e0773df80000000c74455874436f6d6d656e74
+        007465737457612be90000028d4944415478dabdd12f701a5110c7f1a52d0613c34c27e60c06c34ce75560ce60ae0273
+        0673159835988bc09c6186b90acc19cc5660ce504105e62a305450f14ccc456030a9a0939004f287fc8124af4be655b4
+        33116902e22356fd66e70b00a052905469482903b65416d24ac0b632c1501664940d59e5404e2108e5425e79602a1f0a
+        2a004b11145508b6ea404945e0a83e949504543154d4085c3586aa4a402af930b02eaf78005623ec9eddb15bb6640b76
+        c3aed915bb647376c1ced9193b6533366527ec981db1093b6407af219daa2793c97bed4ebbd596da42bbd1aeb52bed52
+        9b6b17daf99b870ff81576cfeed8edcb12db2a258c455a64e686c8ceb2223711428c4d91dfb78439b24561e8082b4651
+        dc75852d3d511af8c2e907a2dc238151282add8e703b91a8b6fbc20ba5a8b562e1d348349a63110409308d558cc5ba6c
+        2072ee6dfda5826a67dae9ca6391976cc16ed835bbfa7f28540af3f3349a13030bfb59b486028bbb26da030b4b3d1b9d
+        ae83e53622b65cac343d741b3e566b017a55c25a2544bfdcc14629c2a0d8c7664122e5636ce5461866c6d8de4e809b5f
+        459eafcb06225b99fa4b0565336d874dd9c953225fb239bb60e7ecec11477fdf545429b267692aed1be4c4592a0f0461
+        6452a56d914b36551b0e791e52ade292ef78d428fa1498013573446484d4daea500811b54ffbd4f929a9bb1753f46344
+        bd6f63ea7f494068af22cfd6650391cbefeacf0c3afd13543bd68e569e1bf998cdd8949de8fb1f12554a562669e90e0d
+        591d64a5d715b2d632a5dfb064a36acba0ecc8661125e55dd9ca7832dcf2657b19c8ce2f92ddbd5046df3bb2f73592fd
+        cf7d39f824a5dc89e5eec7918c3f8ce5f07d02e2ca2af2645d3610d933eb4f0cbaf3585036d10eb583df1adfae93f2de
+        be5f0000000049454e44ae426082
+    ";
+
+    #[test]
+    fn decode_png_handles_external_encoders() {
+        let rgb = decode_png(&hex_bytes(PNG_RGB_FIXED_HUFFMAN)).expect("decode RGB");
+        assert_eq!((rgb.width, rgb.height), (7, 5));
+        for y in 0..5u32 {
+            for x in 0..7u32 {
+                let i = ((y * 7 + x) * 4) as usize;
+                let expected = [
+                    ((x * 37 + y * 11) & 255) as u8,
+                    ((x * x + y * 53) & 255) as u8,
+                    (((x ^ y) * 29) & 255) as u8,
+                    0xFF,
+                ];
+                assert_eq!(rgb.rgba[i..i + 4], expected, "({x}, {y})");
+            }
+        }
+
+        let rgba = decode_png(&hex_bytes(PNG_RGBA_DYNAMIC_HUFFMAN)).expect("decode RGBA");
+        assert_eq!((rgba.width, rgba.height), (24, 24));
+        for y in 0..24u32 {
+            for x in 0..24u32 {
+                let i = ((y * 24 + x) * 4) as usize;
+                let expected = [
+                    ((x * 10) & 255) as u8,
+                    ((y * 10) & 255) as u8,
+                    (((x + y) * 5) & 255) as u8,
+                    (255u32.wrapping_sub(x * y) & 255) as u8,
+                ];
+                assert_eq!(rgba.rgba[i..i + 4], expected, "({x}, {y})");
+            }
+        }
+    }
+
+    #[test]
+    fn decode_pnk_image_matches_frame_cover() {
+        let frame = pink072_wrap(b"image", 0, seed()).expect("wrap");
+        let cover = &frame[HEADER_LEN..HEADER_LEN + COVER_LEN];
+
+        let image =
+            decode_pnk_image(&encode_pnk(&frame).expect("encode_pnk")).expect("decode_pnk_image");
+        assert_eq!((image.width, image.height), (72, 72));
+        assert_eq!(image.rgba, cover);
+
+        // APNGでも既定画像はカバーと一致するので厳格モードを通る
+        let animated = encode_pnk_animated(&frame, seed(), 6, 100).expect("animated");
+        assert_eq!(decode_pnk_image(&animated).expect("apng").rgba, cover);
+        assert!(decode_pnk_strict(&animated, seed()).is_ok());
+
+        // CRCは正しいが圧縮データが壊れている
+        let mut corrupt = hex_bytes(PNG_RGBA_DYNAMIC_HUFFMAN);
+        let idat = PngChunks::new(&corrupt)
+            .unwrap()
+            .map(Result::unwrap)
+            .find(|c| &c.chunk_type == b"IDAT")
+            .map(|c| (c.offset, c.data.len()))
+            .unwrap();
+        corrupt[idat.0 + 8 + 40] ^= 0x55;
+        let crc = crate::png::crc32(&corrupt[idat.0 + 4..idat.0 + 8 + idat.1]);
+        corrupt[idat.0 + 8 + idat.1..idat.0 + 12 + idat.1].copy_from_slice(&crc.to_be_bytes());
+        assert_eq!(decode_png(&corrupt), Err(PinkError::CorruptImageData));
+    }
+
+    /// PNGのIEND直前にチャンクを挿入する（メタデータを足すツールの模倣）
+    fn insert_chunk_before_iend(pnk: &[u8], chunk_type: &[u8; 4], data: &[u8]) -> Vec<u8> {
+        let iend = find_png_end(pnk).expect("png end") - 12;
+        let mut out = pnk[..iend].to_vec();
+        out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        out.extend_from_slice(chunk_type);
+        out.extend_from_slice(data);
+        let mut crc_data = chunk_type.to_vec();
+        crc_data.extend_from_slice(data);
+        out.extend_from_slice(&crate::png::crc32(&crc_data).to_be_bytes());
+        out.extend_from_slice(&pnk[iend..]);
+        out
+    }
+
+    #[test]
+    fn find_pnk_frame_tolerates_metadata_and_trailing_bytes() {
+        let frame = pink072_wrap(b"tolerant", 1, seed()).expect("wrap");
+        let pnk = encode_pnk(&frame).expect("encode_pnk");
+        let pnk = insert_chunk_before_iend(&pnk, b"tEXt", b"Software\0optimizer");
+        let pnk = insert_chunk_before_iend(&pnk, b"eXIf", b"MM\0*");
+        let png_end = find_png_end(&pnk).expect("png end");
+
+        // IENDとフレームの間、フレームの後ろにゴミがある
+        let mut messy = pnk[..png_end].to_vec();
+        messy.extend_from_slice(b"\x01\x02junk");
+        messy.extend_from_slice(&frame);
+        messy.extend_from_slice(b"uploader-trailer");
+
+        let found = find_pnk_frame(&messy, FindOptions::default()).expect("find");
+        assert_eq!(found.source, FrameSource::AfterIend);
+        assert_eq!(found.offset, png_end + 6);
+        assert_eq!(found.leading, 6);
+        assert_eq!(found.trailing, 16);
+        assert_eq!(found.frame.as_ref(), frame.as_slice());
+        let (_, out) = pink072_unwrap(&found.frame).expect("unwrap");
+        assert_eq!(out, b"tolerant");
+
+        // フレームが無い
+        assert_eq!(
+            find_pnk_frame(&pnk[..png_end], FindOptions::default()),
+            Err(PinkError::InvalidFormat)
+        );
+    }
+
+    #[test]
+    fn find_pnk_frame_searches_private_chunk() {
+        let frame = pink072_wrap(b"in a chunk", 0, seed()).expect("wrap");
+        let png = encode_pnk(&frame).expect("encode_pnk");
+        let png = &png[..png.len() - frame.len()];
+
+        let mut chunk = b"pad".to_vec();
+        chunk.extend_from_slice(&frame);
+        let pnk = insert_chunk_before_iend(png, &PNK_CHUNK_TYPE, &chunk);
+
+        assert_eq!(
+            find_pnk_frame(&pnk, FindOptions::default()),
+            Err(PinkError::InvalidFormat)
+        );
+        let found = find_pnk_frame(
+            &pnk,
+            FindOptions {
+                search_chunks: true,
+            },
+        )
+        .expect("find");
+        assert_eq!(found.source, FrameSource::Chunk);
+        assert_eq!(found.leading, 3);
+        assert_eq!(found.trailing, 0);
+        assert_eq!(found.frame.as_ref(), frame.as_slice());
+    }
+
+    #[test]
+    fn chunk_layout_round_trip() {
+        let frame = pink072_wrap(b"inside the png", 1, seed()).expect("wrap");
+        let pnk = encode_pnk_with_layout(&frame, PnkLayout::Chunk).expect("encode_pnk");
+
+        // IENDで終わる正しいPNGで、後ろには何もない
+        assert_eq!(find_png_end(&pnk), Ok(pnk.len()));
+        let types: Vec<[u8; 4]> = PngChunks::new(&pnk)
+            .expect("signature")
+            .map(|c| c.expect("chunk").chunk_type)
+            .collect();
+        assert_eq!(types, [*b"IHDR", *b"IDAT", PNK_CHUNK_TYPE, *b"IEND"]);
+
+        let decoded = decode_pnk(&pnk).expect("decode_pnk");
+        assert_eq!(decoded, frame.as_slice());
+        assert!(decode_pnk_strict(&pnk, seed()).is_ok());
+        let inspection = inspect_pnk(&pnk);
+        assert_eq!(inspection.trailing_len, 0);
+        assert_eq!(
+            inspection
+                .frame
+                .expect("frame")
+                .expect("header")
+                .payload_len,
+            14
+        );
+    }
+
+    #[test]
+    fn chunk_layout_splits_large_frames() {
+        let payload: Vec<u8> = (0..PNK_CHUNK_MAX_LEN + 1000).map(|i| i as u8).collect();
+        let frame = pink072_wrap(&payload, 0, seed()).expect("wrap");
+        let pnk = encode_pnk_with_layout(&frame, PnkLayout::Chunk).expect("encode_pnk");
+
+        let sizes: Vec<usize> = PngChunks::new(&pnk)
+            .expect("signature")
+            .map(|c| c.expect("chunk"))
+            .filter(|c| c.chunk_type == PNK_CHUNK_TYPE)
+            .map(|c| c.data.len())
+            .collect();
+        assert_eq!(sizes, [PNK_CHUNK_MAX_LEN, frame.len() - PNK_CHUNK_MAX_LEN]);
+
+        let decoded = decode_pnk(&pnk).expect("decode_pnk");
+        let (_, out) = pink072_unwrap(&decoded).expect("unwrap");
+        assert_eq!(out, payload);
+
+        let found = find_pnk_frame(
+            &pnk,
+            FindOptions {
+                search_chunks: true,
+            },
+        )
+        .expect("find");
+        assert_eq!(found.frame.as_ref(), frame.as_slice());
+    }
+
+    /// マジックの無い旧形式（v1）のフレームを組み立てる
+    fn legacy_frame(payload: &[u8], payload_type: u8) -> Vec<u8> {
+        let frame = pink072_wrap(payload, payload_type, seed()).expect("wrap");
+        let mut legacy = vec![0u8; HEADER_LEN];
+        legacy[0] = 1;
+        legacy[1] = payload_type;
+        legacy[2] = BLOCK_SIZE as u8;
+        legacy[4..12].copy_from_slice(&(payload.len() as u64).to_le_bytes());
+        legacy.extend_from_slice(&frame[HEADER_LEN..]);
+        legacy
+    }
+
+    #[test]
+    fn legacy_frames_stay_readable() {
+        let legacy = legacy_frame(b"old", 1);
+        let (ptype, out) = pink072_unwrap(&legacy).expect("unwrap legacy");
+        assert_eq!((ptype, out.as_slice()), (1, &b"old"[..]));
+
+        let pnk = encode_pnk(&legacy).expect("encode_pnk");
+        let info = pnk_info(&pnk).expect("pnk_info");
+        assert_eq!(info.frame.version, 1);
+        assert_eq!(info.frame.payload_len, 3);
+        assert!(decode_pnk_strict(&pnk, seed()).is_ok());
+
+        let mut unknown = legacy.clone();
+        unknown[0] = 7;
+        assert_eq!(
+            pink072_unwrap(&unknown),
+            Err(PinkError::UnsupportedVersion { version: 7 })
+        );
+    }
+
+    #[test]
+    fn is_pnk_detects_magic_and_rejects_random_trailers() {
+        let frame = pink072_wrap(b"magic", 2, seed()).expect("wrap");
+        let pnk = encode_pnk(&frame).expect("encode_pnk");
+        assert!(is_pnk(&pnk));
+        assert!(is_pnk(
+            &encode_pnk_with_layout(&frame, PnkLayout::Chunk).expect("encode_pnk")
+        ));
+
+        let info = pnk_info(&pnk).expect("pnk_info");
+        assert_eq!(info.layout, PnkLayout::Trailing);
+        assert_eq!(info.frame.version, FRAME_VERSION);
+        assert_eq!(info.frame.payload_type, 2);
+        assert_eq!(info.frame.payload_len, 5);
+
+        // 普通のPNGの後ろにゴミがあってもPNKとはみなさない
+        let png_end = find_png_end(&pnk).expect("png end");
+        let mut random = pnk[..png_end].to_vec();
+        random.extend((0..200u32).map(|i| (i * 97 + 13) as u8));
+        assert!(!is_pnk(&random));
+        assert_eq!(decode_pnk(&random), Err(PinkError::InvalidFormat));
+        assert!(!is_pnk(&pnk[..png_end]));
+        assert!(!is_pnk(b"not a png"));
+
+        // v2でも未知のバージョンは読めない
+        let mut future = frame.clone();
+        future[4] = 9;
+        assert_eq!(
+            pink072_unwrap(&future),
+            Err(PinkError::UnsupportedVersion { version: 9 })
+        );
+    }
+
+    /// JPEGのマーカーをSOIからEOIまで辿り、SOFの `(幅, 高さ)` を返す
+    fn jpeg_structure(jpeg: &[u8]) -> (usize, usize) {
+        assert_eq!(&jpeg[..2], &[0xFF, 0xD8], "SOI");
+        let mut pos = 2;
+        let mut size = None;
+        let mut scans = 0;
+        loop {
+            assert_eq!(jpeg[pos], 0xFF, "marker at {pos}");
+            while jpeg[pos + 1] == 0xFF {
+                pos += 1;
+            }
+            let marker = jpeg[pos + 1];
+            pos += 2;
+            match marker {
+                0xD9 => break,
+                0x01 | 0xD0..=0xD7 => continue,
+                0xD8 => panic!("second SOI at {pos}"),
+                _ => {}
+            }
+
+            let len = usize::from(u16::from_be_bytes([jpeg[pos], jpeg[pos + 1]]));
+            assert!(
+                len >= 2 && pos + len <= jpeg.len(),
+                "segment length at {pos}"
+            );
+            let segment = &jpeg[pos + 2..pos + len];
+            if matches!(marker, 0xC0..=0xC3) {
+                let height = u16::from_be_bytes([segment[1], segment[2]]);
+                let width = u16::from_be_bytes([segment[3], segment[4]]);
+                size = Some((usize::from(width), usize::from(height)));
+            }
+            pos += len;
+
+            if marker == 0xDA {
+                // エントロピー符号化データ: FF00 とRSTを除くマーカーまで進む
+                scans += 1;
+                while !(jpeg[pos] == 0xFF
+                    && jpeg[pos + 1] != 0x00
+                    && !(0xD0..=0xD7).contains(&jpeg[pos + 1]))
+                {
+                    pos += 1;
+                }
+            }
+        }
+        assert_eq!(pos, jpeg.len(), "EOI is the last marker");
+        assert!(scans > 0, "no SOS");
+        size.expect("no SOF")
+    }
+
+    /// GIFのブロックをトレーラ `0x3B` まで辿り、論理画面の `(幅, 高さ)` を返す
+    fn gif_structure(gif: &[u8]) -> (usize, usize) {
+        fn color_table_len(packed: u8) -> usize {
+            if packed & 0x80 != 0 {
+                3 << ((packed & 0x07) + 1)
+            } else {
+                0
+            }
+        }
+        fn skip_sub_blocks(gif: &[u8], mut pos: usize) -> usize {
+            loop {
+                let len = usize::from(gif[pos]);
+                pos += 1 + len;
+                assert!(pos <= gif.len(), "sub-block past the end");
+                if len == 0 {
+                    return pos;
+                }
+            }
+        }
+
+        assert!(gif.starts_with(b"GIF89a") || gif.starts_with(b"GIF87a"));
+        let width = usize::from(u16::from_le_bytes([gif[6], gif[7]]));
+        let height = usize::from(u16::from_le_bytes([gif[8], gif[9]]));
+        let mut pos = 13 + color_table_len(gif[10]);
+        let mut images = 0;
+        loop {
+            match gif[pos] {
+                0x21 => pos = skip_sub_blocks(gif, pos + 2),
+                0x2C => {
+                    let x = usize::from(u16::from_le_bytes([gif[pos + 1], gif[pos + 2]]));
+                    let y = usize::from(u16::from_le_bytes([gif[pos + 3], gif[pos + 4]]));
+                    let w = usize::from(u16::from_le_bytes([gif[pos + 5], gif[pos + 6]]));
+                    let h = usize::from(u16::from_le_bytes([gif[pos + 7], gif[pos + 8]]));
+                    assert!(
+                        x + w <= width && y + h <= height,
+                        "image outside the screen"
+                    );
+                    pos += 10 + color_table_len(gif[pos + 9]);
+                    assert!((2..=8).contains(&gif[pos]), "LZW minimum code size");
+                    pos = skip_sub_blocks(gif, pos + 1);
+                    images += 1;
+                }
+                0x3B => break,
+                block => panic!("unexpected GIF block 0x{block:02X} at {pos}"),
+            }
+        }
+        assert_eq!(pos + 1, gif.len(), "trailer is the last byte");
+        assert!(images > 0, "no image descriptor");
+        (width, height)
+    }
+
+    /// WebPのRIFFチャンクを辿り、VP8Lの `(幅, 高さ)` を返す
+    fn webp_structure(webp: &[u8]) -> (usize, usize) {
+        assert_eq!(&webp[0..4], b"RIFF");
+        assert_eq!(&webp[8..12], b"WEBP");
+        let riff_len = u32::from_le_bytes(webp[4..8].try_into().unwrap()) as usize;
+        assert_eq!(riff_len, webp.len() - 8, "RIFF size");
+        assert_eq!(riff_len % 2, 0, "RIFF size is even");
+
+        let mut pos = 12;
+        let mut size = None;
+        while pos < webp.len() {
+            let fourcc = &webp[pos..pos + 4];
+            let len = u32::from_le_bytes(webp[pos + 4..pos + 8].try_into().unwrap()) as usize;
+            let data = &webp[pos + 8..pos + 8 + len];
+            if fourcc == b"VP8L" {
+                assert_eq!(data[0], 0x2F, "VP8L signature");
+                let bits = u32::from_le_bytes(data[1..5].try_into().unwrap());
+                assert_eq!(bits >> 29, 0, "VP8L version");
+                let width = (bits & 0x3FFF) as usize + 1;
+                let height = ((bits >> 14) & 0x3FFF) as usize + 1;
+                size = Some((width, height));
+            }
+            // チャンクのデータは偶数バイトに詰められる
+            pos += 8 + len + (len & 1);
+        }
+        assert_eq!(pos, webp.len(), "chunks fill the RIFF container");
+        size.expect("no VP8L chunk")
+    }
+
+    /// BMPのヘッダがファイル長と矛盾しないか確かめ、`(幅, 高さ)` を返す
+    fn bmp_structure(bmp: &[u8]) -> (usize, usize) {
+        let u32_at = |pos: usize| u32::from_le_bytes(bmp[pos..pos + 4].try_into().unwrap());
+        assert_eq!(&bmp[0..2], b"BM");
+        assert_eq!(u32_at(2) as usize, bmp.len(), "file size");
+        assert!((u32_at(10) as usize) < bmp.len(), "pixel data offset");
+        let width = u32_at(18) as i32;
+        let height = u32_at(22) as i32;
+        (
+            width.unsigned_abs() as usize,
+            height.unsigned_abs() as usize,
+        )
+    }
+
+    #[test]
+    fn carriers_round_trip_with_auto_detection() {
+        let frame = pink072_wrap(b"any carrier", 0, seed()).expect("wrap");
+        assert_eq!(
+            encode_carrier(&frame, CarrierFormat::Png).expect("png"),
+            encode_pnk(&frame).expect("encode_pnk")
+        );
+
+        for format in [
+            CarrierFormat::Png,
+            CarrierFormat::Jpeg,
+            CarrierFormat::Gif,
+            CarrierFormat::Bmp,
+            CarrierFormat::WebP,
+        ] {
+            let data = encode_carrier(&frame, format).expect("encode_carrier");
+            assert_eq!(CarrierFormat::detect(&data), Some(format));
+
+            // 画像部分の終端がちょうどフレームの開始位置
+            let carrier = format.carrier();
+            let end = carrier.find_end(&data).expect("find_end");
+            assert_eq!(end, data.len() - frame.len());
+
+            // 画像部分が各形式として正しい構造になっている
+            let image = &data[..end];
+            let size = match format {
+                CarrierFormat::Png => {
+                    let decoded = decode_png(image).expect("decode_png");
+                    (decoded.width as usize, decoded.height as usize)
+                }
+                CarrierFormat::Jpeg => jpeg_structure(image),
+                CarrierFormat::Gif => gif_structure(image),
+                CarrierFormat::Bmp => bmp_structure(image),
+                CarrierFormat::WebP => webp_structure(image),
+            };
+            assert_eq!(size, (COVER_WIDTH, COVER_HEIGHT), "{format:?}");
+            assert_eq!(
+                carrier.find_end(&data[..end - 1]),
+                Err(match format {
+                    CarrierFormat::Png => PinkError::PngMissingIend,
+                    _ => PinkError::MissingImageEnd,
+                })
+            );
+
+            let (detected, decoded) = decode_carrier(&data).expect("decode_carrier");
+            assert_eq!(detected, format);
+            assert_eq!(decoded.as_ref(), frame.as_slice());
+
+            // 画像だけ（フレーム無し）は拒否する
+            assert!(decode_carrier(&data[..end]).is_err());
+        }
+        assert_eq!(
+            decode_carrier(b"not an image"),
+            Err(PinkError::InvalidFormat)
+        );
+    }
+
+    #[test]
+    fn jpeg_end_skips_markers_inside_segments() {
+        let frame = pink072_wrap(b"exif", 0, seed()).expect("wrap");
+        let jpeg = encode_carrier(&frame, CarrierFormat::Jpeg).expect("jpeg");
+
+        // SOIの直後に、EOIを含むAPP1（EXIFサムネイル相当）を挿入
+        let mut with_app1 = jpeg[..2].to_vec();
+        with_app1.extend_from_slice(&[0xFF, 0xE1, 0x00, 0x08, 0xFF, 0xD8, 0xFF, 0xD9, 0x00, 0x00]);
+        with_app1.extend_from_slice(&jpeg[2..]);
+
+        let (_, decoded) = decode_carrier(&with_app1).expect("decode_carrier");
+        assert_eq!(decoded.as_ref(), frame.as_slice());
+    }
+
+    #[test]
+    fn detect_pnk_reads_only_the_header() {
+        let test_dir = Path::new("/tmp/pink072_test_detect");
+        let _ = fs::remove_dir_all(test_dir);
+        fs::create_dir_all(test_dir).unwrap();
+
+        let frame = pink072_wrap(b"detect me", 0, seed()).expect("wrap");
+        let trailing = test_dir.join("trailing.png");
+        let chunk = test_dir.join("chunk.dat");
+        let plain = test_dir.join("plain.png");
+        fs::write(&trailing, encode_pnk(&frame).expect("encode_pnk")).unwrap();
+        fs::write(
+            &chunk,
+            encode_pnk_with_layout(&frame, PnkLayout::Chunk).expect("encode_pnk"),
+        )
+        .unwrap();
+        let pnk = encode_pnk(&frame).expect("encode_pnk");
+        fs::write(&plain, &pnk[..pnk.len() - frame.len()]).unwrap();
+
+        let info = detect_pnk(&trailing).expect("trailing");
+        assert_eq!(info.layout, PnkLayout::Trailing);
+        assert_eq!(info.frame.payload_len, 9);
+        assert_eq!(detect_pnk(&chunk).expect("chunk").layout, PnkLayout::Chunk);
+        assert_eq!(detect_pnk(&plain), None);
+        assert_eq!(detect_pnk(&test_dir.join("missing.png")), None);
+
+        let _ = fs::remove_dir_all(test_dir);
+    }
+
+    #[test]
+    fn scan_reports_pnk_files_in_a_tree() {
+        let test_dir = Path::new("/tmp/pink072_test_scan");
+        let _ = fs::remove_dir_all(test_dir);
+        fs::create_dir_all(test_dir.join("nested/deeper")).unwrap();
+
+        let input = test_dir.join("note.txt");
+        fs::write(&input, b"scan me").unwrap();
+        encode_file(&input, &test_dir.join("note.pnk"), seed()).unwrap();
+        encode_raw(b"raw bytes", &test_dir.join("nested/photo.dat"), seed()).unwrap();
+
+        let frame = pink072_wrap(b"gif payload", 0, seed()).expect("wrap");
+        let gif = encode_carrier(&frame, CarrierFormat::Gif).expect("gif");
+        fs::write(test_dir.join("nested/deeper/anim.gif"), &gif).unwrap();
+
+        // ペイロードが途中で切れたPNK、フレームの無いPNG
+        let pnk = fs::read(test_dir.join("note.pnk")).unwrap();
+        fs::write(test_dir.join("nested/cut.png"), &pnk[..pnk.len() - 3]).unwrap();
+        let png_end = find_png_end(&pnk).unwrap();
+        fs::write(test_dir.join("plain.png"), &pnk[..png_end]).unwrap();
+
+        let mut results: Vec<ScanResult> = scan(test_dir).collect();
+        results.sort_by(|a, b| a.path.cmp(&b.path));
+        let names: Vec<_> = results
+            .iter()
+            .map(|r| r.path.strip_prefix(test_dir).unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "nested/cut.png",
+                "nested/deeper/anim.gif",
+                "nested/photo.dat",
+                "note.pnk"
+            ]
+        );
+
+        let cut = &results[0];
+        assert!(!cut.intact);
+        assert_eq!(cut.file_name.as_deref(), Some("note.txt"));
+
+        let anim = &results[1];
+        assert_eq!(anim.format, CarrierFormat::Gif);
+        assert_eq!(anim.frame.payload_len, 11);
+        assert!(anim.intact);
+
+        let raw = &results[2];
+        assert_eq!(raw.frame.payload_type, PAYLOAD_TYPE_RAW);
+        assert_eq!(raw.file_name, None);
+
+        let note = &results[3];
+        assert_eq!(note.format, CarrierFormat::Png);
+        assert_eq!(note.frame.payload_type, PAYLOAD_TYPE_FILE);
+        assert_eq!(note.file_name.as_deref(), Some("note.txt"));
+        assert!(note.intact);
+
+        // 単一ファイルも渡せる
+        assert_eq!(scan(test_dir.join("note.pnk")).count(), 1);
+        assert_eq!(scan(test_dir.join("missing")).count(), 0);
+
+        let _ = fs::remove_dir_all(test_dir);
     }
 
     #[test]
@@ -95,7 +1098,7 @@ mod tests {
         fs::write(&input_file, b"Hello, Pink072!").unwrap();
 
         // エンコード
-        encode_file(&input_file, &pnk_file, &seed()).unwrap();
+        encode_file(&input_file, &pnk_file, seed()).unwrap();
         assert!(pnk_file.exists());
 
         // デコード
@@ -129,7 +1132,7 @@ mod tests {
         let output_dir = test_dir.join("extracted");
 
         // エンコード
-        encode_folder(&input_folder, &pnk_file, &seed()).unwrap();
+        encode_folder(&input_folder, &pnk_file, seed()).unwrap();
         assert!(pnk_file.exists());
 
         // デコード
@@ -161,7 +1164,7 @@ mod tests {
         fs::write(&input_file, b"auto test content").unwrap();
 
         // 自動エンコード（ファイル）
-        encode_auto(&input_file, &pnk_file, &seed()).unwrap();
+        encode_auto(&input_file, &pnk_file, seed()).unwrap();
 
         // 自動デコード
         let files = decode_auto(&pnk_file, &output_dir).unwrap();
@@ -170,4 +1173,557 @@ mod tests {
 
         let _ = fs::remove_dir_all(test_dir);
     }
+
+    #[test]
+    fn signed_frames_verify_against_keyring() {
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+        let other_key = SigningKey::from_bytes(&[8; 32]);
+        let keyring: Keyring = [signing_key.verifying_key()].into_iter().collect();
+
+        let frame = pink072_wrap(b"authentic", 1, seed()).expect("wrap");
+        let signed = sign_frame(&frame, &signing_key).expect("sign");
+        assert_eq!(signed.len(), frame.len() + SIGNATURE_TRAILER_LEN);
+        assert_eq!(signed[7] & FLAG_SIGNED, FLAG_SIGNED);
+
+        let key_id = KeyId::of(&signing_key.verifying_key());
+        assert_eq!(verify_frame(&signed, &keyring), Ok(key_id));
+        assert_eq!(frame_key_id(&signed), Ok(Some(key_id)));
+        assert_eq!(frame_key_id(&frame), Ok(None));
+        let (_, out) = pink072_unwrap(&signed).expect("unwrap");
+        assert_eq!(out, b"authentic");
+
+        // 署名し直しても同じ長さ（古いトレーラは外す）
+        let resigned = sign_frame(&signed, &other_key).expect("resign");
+        assert_eq!(resigned.len(), signed.len());
+        assert_eq!(
+            verify_frame(&resigned, &keyring),
+            Err(PinkError::UntrustedKey {
+                key_id: KeyId::of(&other_key.verifying_key())
+            })
+        );
+
+        let mut tampered = signed.clone();
+        tampered[frame.len() - 1] ^= 1;
+        assert_eq!(
+            verify_frame(&tampered, &keyring),
+            Err(PinkError::BadSignature)
+        );
+        assert_eq!(verify_frame(&frame, &keyring), Err(PinkError::Unsigned));
+        assert_eq!(
+            verify_frame(&signed[..signed.len() - 1], &keyring),
+            Err(PinkError::TruncatedFrame)
+        );
+
+        // ペイロード長が巨大でもトレーラの位置の計算であふれない
+        let mut huge = signed.clone();
+        huge[8..16].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert_eq!(
+            verify_frame(&huge, &keyring),
+            Err(PinkError::PayloadLengthOverflow)
+        );
+        assert_eq!(frame_key_id(&huge), Err(PinkError::PayloadLengthOverflow));
+        let payload_len = (usize::MAX - HEADER_LEN - COVER_LEN) as u64;
+        huge[8..16].copy_from_slice(&payload_len.to_le_bytes());
+        assert_eq!(
+            verify_frame(&huge, &keyring),
+            Err(PinkError::TruncatedFrame)
+        );
+        assert_eq!(frame_key_id(&huge), Err(PinkError::TruncatedFrame));
+
+        // 署名トレーラもフレームの一部として見つかる
+        let pnk = encode_pnk(&signed).expect("encode_pnk");
+        let mut messy = pnk.clone();
+        messy.extend_from_slice(b"uploader-trailer");
+        let found = find_pnk_frame(&messy, FindOptions::default()).expect("find");
+        assert_eq!(found.frame.as_ref(), signed.as_slice());
+        assert_eq!(found.trailing, 16);
+    }
+
+    #[test]
+    fn decode_auto_can_require_trusted_signatures() {
+        let test_dir = Path::new("/tmp/pink072_test_signed");
+        let _ = fs::remove_dir_all(test_dir);
+        fs::create_dir_all(test_dir).unwrap();
+
+        let signing_key = SigningKey::from_bytes(&[7; 32]);
+        let mut keyring = Keyring::new();
+        keyring.insert(signing_key.verifying_key());
+        let options = DecodeOptions {
+            trusted_keys: Some(&keyring),
+            ..Default::default()
+        };
+
+        let frame = pink072_wrap(b"signed raw", PAYLOAD_TYPE_RAW, seed()).expect("wrap");
+        let signed = sign_frame(&frame, &signing_key).expect("sign");
+        fs::write(
+            test_dir.join("signed.pnk"),
+            encode_pnk(&signed).expect("encode_pnk"),
+        )
+        .unwrap();
+        fs::write(
+            test_dir.join("unsigned.pnk"),
+            encode_pnk(&frame).expect("encode_pnk"),
+        )
+        .unwrap();
+
+        let output_dir = test_dir.join("out");
+        let files = decode_auto_with(&test_dir.join("signed.pnk"), &output_dir, &options).unwrap();
+        assert_eq!(files, ["data.bin"]);
+        assert_eq!(
+            fs::read(output_dir.join("data.bin")).unwrap(),
+            b"signed raw"
+        );
+
+        let err = decode_auto_with(&test_dir.join("unsigned.pnk"), &output_dir, &options)
+            .expect_err("unsigned");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        // 鍵束を指定しなければ署名の有無は問わない
+        assert!(decode_auto(&test_dir.join("unsigned.pnk"), &output_dir).is_ok());
+        assert!(decode_auto(&test_dir.join("signed.pnk"), &output_dir).is_ok());
+
+        let _ = fs::remove_dir_all(test_dir);
+    }
+
+    #[test]
+    fn encrypted_frames_open_only_for_recipients() {
+        let alice = RecipientSecret::from([1; 32]);
+        let bob = RecipientSecret::from([2; 32]);
+        let eve = RecipientSecret::from([3; 32]);
+        let recipients = [RecipientKey::from(&alice), RecipientKey::from(&bob)];
+
+        let frame = pink072_wrap_encrypted(b"for your eyes", 1, seed(), &recipients).expect("wrap");
+        let header = crate::core::FrameHeader::parse(&frame).expect("header");
+        assert!(header.is_encrypted());
+        assert_eq!(header.payload_len, b"for your eyes".len() as u64 + 16);
+        assert_eq!(
+            frame_recipients(&frame),
+            Ok(recipients.iter().map(KeyId::of_recipient).collect())
+        );
+
+        for secret in [&alice, &bob] {
+            let (payload_type, out) = pink072_unwrap_encrypted(&frame, secret).expect("unwrap");
+            assert_eq!(payload_type, 1);
+            assert_eq!(out, b"for your eyes");
+        }
+        assert_eq!(
+            pink072_unwrap_encrypted(&frame, &eve),
+            Err(PinkError::NotRecipient)
+        );
+        assert_eq!(pink072_unwrap(&frame), Err(PinkError::Encrypted));
+
+        let mut tampered = frame.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert_eq!(
+            pink072_unwrap_encrypted(&tampered, &alice),
+            Err(PinkError::DecryptionFailed)
+        );
+
+        // 暗号化の後に署名しても復号でき、PNKとしても往復できる
+        let signed = sign_frame(&frame, &SigningKey::from_bytes(&[7; 32])).expect("sign");
+        let pnk = encode_pnk(&signed).expect("encode_pnk");
+        let decoded = decode_pnk(&pnk).expect("decode");
+        let (_, out) = pink072_unwrap_encrypted(&decoded, &bob).expect("unwrap signed");
+        assert_eq!(out, b"for your eyes");
+
+        // 暗号化されていないフレームは秘密鍵があってもそのまま読める
+        let plain = pink072_wrap(b"plain", 0, seed()).expect("wrap");
+        assert_eq!(
+            pink072_unwrap_encrypted(&plain, &eve),
+            Ok((0, b"plain".to_vec()))
+        );
+        assert_eq!(frame_recipients(&plain), Ok(Vec::new()));
+        assert_eq!(
+            pink072_wrap_encrypted(b"x", 0, seed(), &[]),
+            Err(PinkError::NoRecipients)
+        );
+    }
+
+    #[test]
+    fn encrypted_files_round_trip() {
+        let test_dir = Path::new("/tmp/pink072_test_encrypted");
+        let _ = fs::remove_dir_all(test_dir);
+        fs::create_dir_all(test_dir).unwrap();
+
+        let secret = RecipientSecret::from([4; 32]);
+        let recipients = [RecipientKey::from(&secret)];
+        let input = test_dir.join("note.txt");
+        fs::write(&input, b"encrypted note").unwrap();
+        let pnk_path = test_dir.join("note.pnk");
+        encode_file_with(
+            &input,
+            &pnk_path,
+            seed(),
+            &EncodeOptions {
+                recipients: &recipients,
+            },
+        )
+        .unwrap();
+
+        // 秘密鍵が無ければ読めない
+        let output_dir = test_dir.join("out");
+        let err = decode_file(&pnk_path, &output_dir).expect_err("encrypted");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+        let options = DecodeOptions {
+            secret_key: Some(&secret),
+            ..Default::default()
+        };
+        let name = decode_file_with(&pnk_path, &output_dir, &options).unwrap();
+        assert_eq!(name, "note.txt");
+        assert_eq!(
+            fs::read(output_dir.join("note.txt")).unwrap(),
+            b"encrypted note"
+        );
+
+        let _ = fs::remove_dir_all(test_dir);
+    }
+
+    #[test]
+    fn zip_payload_builds_and_lists_in_memory() {
+        let zip = build_zip_payload([("a.txt", &b"alpha"[..]), ("dir/b.bin", &[0u8, 1, 2][..])])
+            .expect("zip");
+        let frame = pink072_wrap(&zip, PAYLOAD_TYPE_ZIP, seed()).expect("wrap");
+        let (_, payload) = pink072_unwrap(&frame).expect("unwrap");
+
+        let entries = list_zip_entries(&payload).expect("list");
+        assert_eq!(
+            entries,
+            [
+                ZipEntry {
+                    path: "a.txt".to_string(),
+                    size: 5,
+                    is_dir: false
+                },
+                ZipEntry {
+                    path: "dir/b.bin".to_string(),
+                    size: 3,
+                    is_dir: false
+                },
+            ]
+        );
+        assert_eq!(read_zip_entry(&payload, "dir/b.bin").unwrap(), [0, 1, 2]);
+        assert!(read_zip_entry(&payload, "missing").is_err());
+
+        for path in ["", "/etc/passwd", "../up", "a/../../b", "a//b"] {
+            let err = build_zip_payload([(path, &b""[..])]).expect_err(path);
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        }
+    }
+
+    #[test]
+    fn error_codes_are_stable() {
+        assert_eq!(PinkError::TruncatedFrame.code(), "TRUNCATED_FRAME");
+        assert_eq!(
+            PinkError::PngCrcMismatch { offset: 33 }.code(),
+            "PNG_CRC_MISMATCH"
+        );
+        assert_eq!(
+            PinkError::UntrustedKey {
+                key_id: KeyId([0; 8])
+            }
+            .code(),
+            "UNTRUSTED_KEY"
+        );
+        assert_eq!(PinkError::NotRecipient.code(), "NOT_RECIPIENT");
+        assert_eq!(PinkError::EncryptionFailed.code(), "ENCRYPTION_FAILED");
+    }
+
+    #[test]
+    fn pnk_decoder_streams_both_layouts() {
+        let payload: Vec<u8> = (0..5000u32).map(|i| (i * 7) as u8).collect();
+        let frame = pink072_wrap(&payload, 2, seed()).expect("wrap");
+        let signed = sign_frame(&frame, &SigningKey::from_bytes(&[7; 32])).expect("sign");
+
+        for frame in [&frame, &signed] {
+            for layout in [PnkLayout::Trailing, PnkLayout::Chunk] {
+                let pnk = encode_pnk_with_layout(frame, layout).expect("encode_pnk");
+                for step in [1, 13, 4096, pnk.len()] {
+                    let mut decoder = PnkDecoder::new();
+                    let mut events = Vec::new();
+                    for part in pnk.chunks(step) {
+                        events.extend(decoder.feed(part).expect("feed"));
+                    }
+                    assert_eq!(decoder.finish(), Ok(()));
+
+                    let Some(PnkEvent::Header(info)) = events.first() else {
+                        panic!("expected a header first: {events:?}");
+                    };
+                    assert_eq!(info.payload_type, 2);
+                    assert_eq!(info.payload_len, payload.len() as u64);
+                    let Some(PnkEvent::CoverReady { width, rgba, .. }) = events.get(1) else {
+                        panic!("expected the cover second");
+                    };
+                    assert_eq!(*width, 72);
+                    assert_eq!(rgba.as_slice(), &frame[HEADER_LEN..HEADER_LEN + COVER_LEN]);
+                    assert_eq!(events.last(), Some(&PnkEvent::Done));
+
+                    let streamed: Vec<u8> = events
+                        .iter()
+                        .filter_map(|event| match event {
+                            PnkEvent::PayloadChunk(data) => Some(data.as_slice()),
+                            _ => None,
+                        })
+                        .flatten()
+                        .copied()
+                        .collect();
+                    assert_eq!(streamed, payload);
+                }
+            }
+        }
+
+        // 途中で切れた入力・壊れたCRC
+        let pnk = encode_pnk(&frame).expect("encode_pnk");
+        let mut decoder = PnkDecoder::new();
+        decoder.feed(&pnk[..pnk.len() - 10]).expect("feed");
+        assert!(!decoder.is_done());
+        assert_eq!(decoder.finish(), Err(PinkError::TruncatedFrame));
+
+        let mut decoder = PnkDecoder::new();
+        decoder.feed(&pnk[..20]).expect("feed");
+        assert_eq!(decoder.finish(), Err(PinkError::PngMissingIend));
+
+        let mut corrupt = pnk.clone();
+        corrupt[20] ^= 1;
+        let mut decoder = PnkDecoder::new();
+        assert_eq!(
+            decoder.feed(&corrupt),
+            Err(PinkError::PngCrcMismatch { offset: 8 })
+        );
+        assert_eq!(
+            decoder.feed(b"more"),
+            Err(PinkError::PngCrcMismatch { offset: 8 })
+        );
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn async_file_api_matches_sync() {
+        fn assert_send<T: Send>(_: &T) {}
+
+        let test_dir = Path::new("/tmp/pink072_test_async");
+        let _ = fs::remove_dir_all(test_dir);
+        let folder = test_dir.join("folder");
+        fs::create_dir_all(folder.join("sub")).unwrap();
+        fs::write(folder.join("a.txt"), b"alpha").unwrap();
+        fs::write(folder.join("sub/b.txt"), b"beta").unwrap();
+        let input = test_dir.join("note.txt");
+        fs::write(&input, b"Hello, async!").unwrap();
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            // ファイルはストリームで書くが、結果は同期版と同じバイト列
+            let async_pnk = test_dir.join("async.pnk");
+            let future = encode_file_async(&input, &async_pnk, seed());
+            assert_send(&future);
+            future.await.unwrap();
+            let sync_pnk = test_dir.join("sync.pnk");
+            encode_file(&input, &sync_pnk, seed()).unwrap();
+            assert_eq!(fs::read(&async_pnk).unwrap(), fs::read(&sync_pnk).unwrap());
+
+            let out = test_dir.join("out_file");
+            let future = decode_auto_async(&async_pnk, &out);
+            assert_send(&future);
+            assert_eq!(future.await.unwrap(), vec!["note.txt".to_string()]);
+            assert_eq!(fs::read(out.join("note.txt")).unwrap(), b"Hello, async!");
+
+            let folder_pnk = test_dir.join("folder.pnk");
+            encode_folder_async(&folder, &folder_pnk, seed())
+                .await
+                .unwrap();
+            let out = test_dir.join("out_folder");
+            decode_auto_async(&folder_pnk, &out).await.unwrap();
+            assert_eq!(fs::read(out.join("sub/b.txt")).unwrap(), b"beta");
+
+            // 任意の AsyncWrite に書き出し、AsyncRead から読み戻す
+            let mut body = Vec::new();
+            write_pnk_async(&b"streamed"[..], 8, PAYLOAD_TYPE_RAW, seed(), &mut body)
+                .await
+                .unwrap();
+            let frame = pink072_wrap(b"streamed", PAYLOAD_TYPE_RAW, seed()).unwrap();
+            assert_eq!(body, encode_pnk(&frame).expect("encode_pnk"));
+            let (payload_type, payload) =
+                read_payload_async(body.as_slice(), &DecodeOptions::default())
+                    .await
+                    .unwrap();
+            assert_eq!(
+                (payload_type, payload.as_slice()),
+                (PAYLOAD_TYPE_RAW, &b"streamed"[..])
+            );
+
+            let err = write_pnk_async(&b"short"[..], 8, PAYLOAD_TYPE_RAW, seed(), &mut Vec::new())
+                .await
+                .unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+        });
+
+        let _ = fs::remove_dir_all(test_dir);
+    }
+
+    #[test]
+    fn decode_rejects_paths_outside_the_output_dir() {
+        let test_dir = Path::new("/tmp/pink072_test_traversal");
+        let _ = fs::remove_dir_all(test_dir);
+        let output_dir = test_dir.join("out");
+        fs::create_dir_all(&output_dir).unwrap();
+        let pnk_path = test_dir.join("evil.pnk");
+
+        for name in [
+            "../escape.txt",
+            "/tmp/pink072_test_traversal/abs.txt",
+            "a/b.txt",
+            "..",
+        ] {
+            let payload = file::build_file_payload(name, b"evil");
+            let frame = pink072_wrap(&payload, PAYLOAD_TYPE_FILE, seed()).unwrap();
+            fs::write(&pnk_path, encode_pnk(&frame).expect("encode_pnk")).unwrap();
+            let err = decode_auto(&pnk_path, &output_dir).unwrap_err();
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData, "{name}");
+            assert!(decode_file(&pnk_path, &output_dir).is_err(), "{name}");
+        }
+
+        let mut zip_data = std::io::Cursor::new(Vec::new());
+        let mut zip = zip::ZipWriter::new(&mut zip_data);
+        zip.start_file("../escape.txt", zip::write::SimpleFileOptions::default())
+            .unwrap();
+        std::io::Write::write_all(&mut zip, b"evil").unwrap();
+        zip.finish().unwrap();
+        let frame = pink072_wrap(zip_data.get_ref(), PAYLOAD_TYPE_ZIP, seed()).unwrap();
+        fs::write(&pnk_path, encode_pnk(&frame).expect("encode_pnk")).unwrap();
+        assert!(decode_auto(&pnk_path, &output_dir).is_err());
+
+        assert!(!test_dir.join("escape.txt").exists());
+        assert!(!test_dir.join("abs.txt").exists());
+        assert_eq!(fs::read_dir(&output_dir).unwrap().count(), 0);
+        let _ = fs::remove_dir_all(test_dir);
+    }
+
+    proptest! {
+        #[test]
+        fn frames_round_trip_for_any_payload(
+            payload in proptest::collection::vec(any::<u8>(), 0..2048),
+            payload_type in any::<u8>(),
+            seed9 in any::<[u8; 9]>(),
+        ) {
+            let frame = pink072_wrap(&payload, payload_type, seed9).unwrap();
+            prop_assert_eq!(pink072_unwrap(&frame).unwrap(), (payload_type, payload.clone()));
+
+            let pnk = encode_pnk(&frame).expect("encode_pnk");
+            let decoded = decode_pnk(&pnk).unwrap();
+            prop_assert_eq!(decoded.as_ref(), frame.as_slice());
+            prop_assert_eq!(find_png_end(&pnk).unwrap() + frame.len(), pnk.len());
+
+            let file_payload = file::build_file_payload("name.bin", &payload);
+            let (name, data) = file::parse_file_payload(&file_payload).unwrap();
+            prop_assert_eq!((name.as_str(), data), ("name.bin", payload.as_slice()));
+        }
+
+        #[test]
+        fn parsers_never_panic_on_arbitrary_input(
+            data in proptest::collection::vec(any::<u8>(), 0..512),
+        ) {
+            let _ = pink072_unwrap(&data);
+            let _ = find_png_end(&data);
+            let _ = decode_pnk(&data);
+            let _ = decode_carrier(&data);
+            let _ = file::parse_file_payload(&data);
+            if let Ok(entries) = list_zip_entries(&data) {
+                for entry in entries {
+                    let _ = read_zip_entry(&data, &entry.path);
+                }
+            }
+            let _ = PnkDecoder::new().feed(&data);
+        }
+
+        #[test]
+        fn zip_readers_never_panic_on_corrupted_zip(
+            flips in proptest::collection::vec((any::<prop::sample::Index>(), any::<u8>()), 1..8),
+        ) {
+            let mut zip = build_zip_payload([
+                ("a.txt", &b"alpha"[..]),
+                ("dir/b.bin", &[0u8, 1, 2][..]),
+            ])
+            .unwrap();
+            for (index, value) in flips {
+                let i = index.index(zip.len());
+                zip[i] ^= value;
+            }
+            if let Ok(entries) = list_zip_entries(&zip) {
+                for entry in entries {
+                    let _ = read_zip_entry(&zip, &entry.path);
+                }
+            }
+        }
+
+        #[test]
+        fn parsers_never_panic_on_corrupted_pnk(
+            payload in proptest::collection::vec(any::<u8>(), 0..256),
+            flips in proptest::collection::vec((any::<prop::sample::Index>(), any::<u8>()), 1..8),
+            cut in any::<prop::sample::Index>(),
+        ) {
+            let frame = pink072_wrap(&payload, PAYLOAD_TYPE_RAW, seed()).unwrap();
+            let mut pnk = encode_pnk_with_layout(&frame, PnkLayout::Chunk).expect("encode_pnk");
+            for (index, value) in flips {
+                let i = index.index(pnk.len());
+                pnk[i] ^= value;
+            }
+            pnk.truncate(cut.index(pnk.len() + 1));
+
+            if let Ok(frame) = decode_pnk(&pnk) {
+                let _ = pink072_unwrap(&frame);
+            }
+            let _ = find_png_end(&pnk);
+            let _ = inspect_pnk(&pnk);
+            let mut decoder = PnkDecoder::new();
+            let _ = decoder.feed(&pnk);
+            let _ = decoder.finish();
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(32))]
+
+        #[test]
+        fn decode_never_writes_outside_the_output_dir(name in "[./a-z\\\\]{1,12}") {
+            let test_dir = std::env::temp_dir().join("pink072_test_prop_traversal");
+            let _ = fs::remove_dir_all(&test_dir);
+            let output_dir = test_dir.join("out");
+            fs::create_dir_all(&output_dir).unwrap();
+            let pnk_path = test_dir.join("input.pnk");
+
+            let payload = file::build_file_payload(&name, b"data");
+            let frame = pink072_wrap(&payload, PAYLOAD_TYPE_FILE, seed()).unwrap();
+            fs::write(&pnk_path, encode_pnk(&frame).expect("encode_pnk")).unwrap();
+            if let Ok(names) = decode_auto(&pnk_path, &output_dir) {
+                prop_assert_eq!(&names, &vec![name.clone()]);
+                prop_assert!(output_dir.join(&name).is_file());
+            }
+
+            let mut entries: Vec<_> = fs::read_dir(&test_dir)
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name())
+                .collect();
+            entries.sort();
+            prop_assert_eq!(entries, ["input.pnk", "out"]);
+            let _ = fs::remove_dir_all(&test_dir);
+        }
+    }
+
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn wasm_encode_pnk_rejects_invalid_frames() {
+        let frame = pink072_wrap(b"wasm", 0, seed()).expect("wrap");
+        let pnk = wasm::wasm_encode_pnk(&frame).expect("wasm_encode_pnk");
+        assert_eq!(pnk, encode_pnk(&frame).expect("encode_pnk"));
+
+        let err = wasm::wasm_encode_pnk(&frame[..10]).unwrap_err();
+        assert_eq!(err.code(), "FRAME_TOO_SMALL");
+        let mut unknown = frame;
+        unknown[4] = 9;
+        let err = wasm::wasm_encode_pnk(&unknown).unwrap_err();
+        assert_eq!(err.code(), "UNSUPPORTED_VERSION");
+    }
 }
@@ -0,0 +1,88 @@
+//! PNKからPINK-072フレームを探す（メタデータ挿入や末尾追記に耐える）
+
+use alloc::{borrow::Cow, vec::Vec};
+
+use crate::core::plausible_frame_len;
+use crate::error::PinkError;
+use crate::png::{PngChunks, PNK_CHUNK_TYPE};
+
+/// `find_pnk_frame` の探索オプション
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct FindOptions {
+    /// IEND前の `pnKa` チャンクの中も探す
+    pub search_chunks: bool,
+}
+
+/// フレームが見つかった場所
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameSource {
+    /// IENDの後ろ
+    AfterIend,
+    /// `pnKa` チャンクの中（複数チャンクは連結される）
+    Chunk,
+}
+
+/// `find_pnk_frame` の結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FoundFrame<'a> {
+    /// フレーム本体（ヘッダからペイロード末尾まで）
+    pub frame: Cow<'a, [u8]>,
+    pub source: FrameSource,
+    /// フレーム（チャンクの場合は最初の `pnKa` チャンク）の開始オフセット
+    pub offset: usize,
+    /// IEND（チャンクの場合はチャンクデータ先頭）からフレームまでの余分なバイト数
+    pub leading: usize,
+    /// フレームの後ろに続く余分なバイト数
+    pub trailing: usize,
+}
+
+/// PNKからフレームを探す
+///
+/// IENDの直後にフレームが無い場合でも、IEND以降をフレームヘッダの形で走査する。
+/// `options.search_chunks` が有効なら `pnKa` チャンクを優先して調べる。
+pub fn find_pnk_frame(data: &[u8], options: FindOptions) -> Result<FoundFrame<'_>, PinkError> {
+    let mut png_end = None;
+    let mut chunk_offset = None;
+    let mut chunk_data = Vec::new();
+    for chunk in PngChunks::new(data)? {
+        let chunk = chunk?;
+        if chunk.chunk_type == PNK_CHUNK_TYPE {
+            chunk_offset.get_or_insert(chunk.offset);
+            chunk_data.extend_from_slice(chunk.data);
+        } else if &chunk.chunk_type == b"IEND" {
+            png_end = Some(chunk.end());
+        }
+    }
+    let png_end = png_end.ok_or(PinkError::PngMissingIend)?;
+
+    if let (true, Some(offset)) = (options.search_chunks, chunk_offset) {
+        if let Some(found) = scan(&chunk_data) {
+            let (leading, frame_len) = found;
+            let trailing = chunk_data.len() - leading - frame_len;
+            chunk_data.truncate(leading + frame_len);
+            chunk_data.drain(..leading);
+            return Ok(FoundFrame {
+                frame: Cow::Owned(chunk_data),
+                source: FrameSource::Chunk,
+                offset,
+                leading,
+                trailing,
+            });
+        }
+    }
+
+    let tail = &data[png_end..];
+    let (leading, frame_len) = scan(tail).ok_or(PinkError::InvalidFormat)?;
+    Ok(FoundFrame {
+        frame: Cow::Borrowed(&tail[leading..leading + frame_len]),
+        source: FrameSource::AfterIend,
+        offset: png_end + leading,
+        leading,
+        trailing: tail.len() - leading - frame_len,
+    })
+}
+
+/// 先頭から順にフレームらしい位置を探し、(開始位置, フレーム長) を返す
+fn scan(data: &[u8]) -> Option<(usize, usize)> {
+    (0..data.len()).find_map(|start| plausible_frame_len(&data[start..]).map(|len| (start, len)))
+}
@@ -1,3 +1,11 @@
+//! 固定小数点（Q16.16）によるパーリンノイズ
+//!
+//! 浮動小数点を一切使わないため、ターゲット（wasm / x86 FMA等）や
+//! 最適化レベルに依存せず、同じシードから常にビット単位で同一の結果を返す。
+
+/// 固定小数点の1.0（Q16.16）
+pub const FIXED_ONE: i64 = 1 << 16;
+
 #[inline]
 pub fn xorshift64(state: &mut u64) -> u64 {
     *state ^= *state << 13;
@@ -6,7 +14,7 @@ pub fn xorshift64(state: &mut u64) -> u64 {
     *state
 }
 
-pub fn seed9_to_u64(seed9: &[u8]) -> u64 {
+pub fn seed9_to_u64(seed9: &[u8; 9]) -> u64 {
     let mut first = [0u8; 8];
     first.copy_from_slice(&seed9[..8]);
     let mut state = u64::from_le_bytes(first);
@@ -25,55 +33,62 @@ pub fn generate_permutation(state: &mut u64) -> [u8; 256] {
     perm
 }
 
-/// パーリンノイズ用の勾配ベクトル（8方向）
-const GRADIENTS: [(f32, f32); 8] = {
-    const D: f32 = std::f32::consts::FRAC_1_SQRT_2;
-    [
-        (1.0, 0.0),
-        (-1.0, 0.0),
-        (0.0, 1.0),
-        (0.0, -1.0),
-        (D, D),
-        (-D, D),
-        (D, -D),
-        (-D, -D),
-    ]
-};
+/// 1/√2 を Q16.16 に丸めた値
+const D: i64 = 46341;
+
+/// パーリンノイズ用の勾配ベクトル（8方向、Q16.16）
+const GRADIENTS: [(i64, i64); 8] = [
+    (FIXED_ONE, 0),
+    (-FIXED_ONE, 0),
+    (0, FIXED_ONE),
+    (0, -FIXED_ONE),
+    (D, D),
+    (-D, D),
+    (D, -D),
+    (-D, -D),
+];
 
+/// 6t^5 - 15t^4 + 10t^3（t は 0〜1 の Q16.16）
 #[inline]
-fn fade(t: f32) -> f32 {
-    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+fn fade(t: i64) -> i64 {
+    let t3 = (((t * t) >> 16) * t) >> 16;
+    let inner = ((t * (6 * t - 15 * FIXED_ONE)) >> 16) + 10 * FIXED_ONE;
+    (t3 * inner) >> 16
 }
 
 #[inline]
-fn lerp(a: f32, b: f32, t: f32) -> f32 {
-    a + t * (b - a)
+fn lerp(a: i64, b: i64, t: i64) -> i64 {
+    a + (((b - a) * t) >> 16)
 }
 
 #[inline]
-fn grad(hash: u8, x: f32, y: f32) -> f32 {
+fn grad(hash: u8, x: i64, y: i64) -> i64 {
     let (gx, gy) = GRADIENTS[(hash & 7) as usize];
-    gx * x + gy * y
+    (gx * x + gy * y) >> 16
 }
 
-/// 2Dパーリンノイズ（0.0〜1.0を返す）
+/// 2Dパーリンノイズ（座標は Q16.16、0〜`FIXED_ONE` を返す）
 #[inline]
-pub fn perlin2d(x: f32, y: f32, perm: &[u8; 256]) -> f32 {
-    let xi = (x.floor() as i32) & 255;
-    let yi = (y.floor() as i32) & 255;
-    let xf = x - x.floor();
-    let yf = y - y.floor();
+pub fn perlin2d(x: i64, y: i64, perm: &[u8; 256]) -> i64 {
+    let xi = ((x >> 16) & 255) as usize;
+    let yi = ((y >> 16) & 255) as usize;
+    let xf = x & (FIXED_ONE - 1);
+    let yf = y & (FIXED_ONE - 1);
 
     let u = fade(xf);
     let v = fade(yf);
 
-    let aa = perm[(perm[xi as usize] as usize + yi as usize) & 255];
-    let ab = perm[(perm[xi as usize] as usize + (yi + 1) as usize) & 255];
-    let ba = perm[(perm[((xi + 1) & 255) as usize] as usize + yi as usize) & 255];
-    let bb = perm[(perm[((xi + 1) & 255) as usize] as usize + (yi + 1) as usize) & 255];
+    let aa = perm[(perm[xi] as usize + yi) & 255];
+    let ab = perm[(perm[xi] as usize + yi + 1) & 255];
+    let ba = perm[(perm[(xi + 1) & 255] as usize + yi) & 255];
+    let bb = perm[(perm[(xi + 1) & 255] as usize + yi + 1) & 255];
 
-    let x1 = lerp(grad(aa, xf, yf), grad(ba, xf - 1.0, yf), u);
-    let x2 = lerp(grad(ab, xf, yf - 1.0), grad(bb, xf - 1.0, yf - 1.0), u);
+    let x1 = lerp(grad(aa, xf, yf), grad(ba, xf - FIXED_ONE, yf), u);
+    let x2 = lerp(
+        grad(ab, xf, yf - FIXED_ONE),
+        grad(bb, xf - FIXED_ONE, yf - FIXED_ONE),
+        u,
+    );
 
-    (lerp(x1, x2, v) + 1.0) * 0.5
+    (lerp(x1, x2, v) + FIXED_ONE) >> 1
 }
@@ -1,13 +1,42 @@
-//! 最小限のPNGエンコーダ/デコーダ（72x72 RGBA専用）
+//! 最小限のPNGエンコーダ/デコーダ（8bit RGBA）
 
-use crate::constants::{COVER_HEIGHT, COVER_LEN, COVER_WIDTH};
+use alloc::{borrow::Cow, vec, vec::Vec};
+
+use crate::constants::{COVER_HEIGHT, COVER_WIDTH, FRAME_MAGIC};
+use crate::core::{verify_cover, FrameHeader, FrameInfo};
+use crate::cover::render_cover_apng;
 use crate::error::PinkError;
+use crate::inflate::zlib_decompress;
+use crate::seed::Seed9;
+
+pub(crate) const PNG_SIGNATURE: [u8; 8] = [0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// フレームを格納する専用の補助チャンク（ancillary / private / safe-to-copy）
+pub const PNK_CHUNK_TYPE: [u8; 4] = *b"pnKa";
+
+/// `pnKa` チャンク1つあたりの最大データ長
+///
+/// libpng等は大きな補助チャンクを読み飛ばす・拒否するため、仕様上限（2^31 - 1）より
+/// 十分小さい1MiBごとに分割する。
+pub const PNK_CHUNK_MAX_LEN: usize = 1 << 20;
 
-const PNG_SIGNATURE: [u8; 8] = [0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+/// PNKのレイアウト
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PnkLayout {
+    /// `[PNG][フレーム]`: IENDの後ろにフレームを追記（従来形式）
+    #[default]
+    Trailing,
+    /// IEND直前の `pnKa` チャンクにフレームを格納（PNGとして完結する）
+    Chunk,
+}
 
 /// CRC32（PNG標準）
-fn crc32(data: &[u8]) -> u32 {
-    let mut crc = 0xFFFF_FFFFu32;
+pub(crate) fn crc32(data: &[u8]) -> u32 {
+    !crc32_update(0xFFFF_FFFF, data)
+}
+
+/// CRC32を少しずつ計算する（初期値 `0xFFFF_FFFF`、最後に反転する）
+pub(crate) fn crc32_update(mut crc: u32, data: &[u8]) -> u32 {
     for &byte in data {
         crc ^= byte as u32;
         for _ in 0..8 {
@@ -18,11 +47,11 @@ fn crc32(data: &[u8]) -> u32 {
             };
         }
     }
-    !crc
+    crc
 }
 
 /// Adler32（zlib用）
-fn adler32(data: &[u8]) -> u32 {
+pub(crate) fn adler32(data: &[u8]) -> u32 {
     let mut a = 1u32;
     let mut b = 0u32;
     for &byte in data {
@@ -42,33 +71,91 @@ fn write_chunk(out: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
     out.extend_from_slice(&crc32(&crc_data).to_be_bytes());
 }
 
-/// 72x72 RGBAをPNGにエンコード
-pub fn encode_png(rgba: &[u8]) -> Vec<u8> {
-    debug_assert_eq!(rgba.len(), COVER_LEN);
+/// 任意サイズのRGBAをPNGにエンコード
+pub fn encode_png_rgba(rgba: &[u8], width: usize, height: usize) -> Vec<u8> {
+    debug_assert_eq!(rgba.len(), width * height * 4);
+
+    let mut out = Vec::new();
+    out.extend_from_slice(&PNG_SIGNATURE);
+    write_ihdr(&mut out, width, height);
+    write_chunk(&mut out, b"IDAT", &zlib_image_data(rgba, width, height));
+    write_chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+/// 複数フレームのRGBAをAPNGにエンコード
+///
+/// 1フレーム目は通常のIDATとして書き込むため、APNG非対応のビューアでは
+/// 静止画として表示される。`delay_ms` は各フレームの表示時間、無限ループ。
+pub fn encode_apng_rgba(frames: &[Vec<u8>], width: usize, height: usize, delay_ms: u16) -> Vec<u8> {
+    debug_assert!(!frames.is_empty());
 
     let mut out = Vec::new();
     out.extend_from_slice(&PNG_SIGNATURE);
+    write_ihdr(&mut out, width, height);
+
+    // acTL: フレーム数 + ループ回数（0 = 無限）
+    let mut actl = Vec::with_capacity(8);
+    actl.extend_from_slice(&(frames.len() as u32).to_be_bytes());
+    actl.extend_from_slice(&0u32.to_be_bytes());
+    write_chunk(&mut out, b"acTL", &actl);
+
+    // fcTL と fdAT は共通の連番を使う
+    let mut sequence = 0u32;
+    for (i, rgba) in frames.iter().enumerate() {
+        debug_assert_eq!(rgba.len(), width * height * 4);
 
-    // IHDR
+        let mut fctl = Vec::with_capacity(26);
+        fctl.extend_from_slice(&sequence.to_be_bytes());
+        fctl.extend_from_slice(&(width as u32).to_be_bytes());
+        fctl.extend_from_slice(&(height as u32).to_be_bytes());
+        fctl.extend_from_slice(&0u32.to_be_bytes()); // x offset
+        fctl.extend_from_slice(&0u32.to_be_bytes()); // y offset
+        fctl.extend_from_slice(&delay_ms.to_be_bytes()); // delay numerator
+        fctl.extend_from_slice(&1000u16.to_be_bytes()); // delay denominator
+        fctl.push(0); // dispose op: none
+        fctl.push(0); // blend op: source
+        write_chunk(&mut out, b"fcTL", &fctl);
+        sequence += 1;
+
+        let zlib = zlib_image_data(rgba, width, height);
+        if i == 0 {
+            write_chunk(&mut out, b"IDAT", &zlib);
+        } else {
+            let mut fdat = Vec::with_capacity(4 + zlib.len());
+            fdat.extend_from_slice(&sequence.to_be_bytes());
+            fdat.extend_from_slice(&zlib);
+            write_chunk(&mut out, b"fdAT", &fdat);
+            sequence += 1;
+        }
+    }
+
+    write_chunk(&mut out, b"IEND", &[]);
+    out
+}
+
+/// IHDRチャンク（8bit RGBA）を書き込み
+fn write_ihdr(out: &mut Vec<u8>, width: usize, height: usize) {
     let mut ihdr = Vec::with_capacity(13);
-    ihdr.extend_from_slice(&(COVER_WIDTH as u32).to_be_bytes());
-    ihdr.extend_from_slice(&(COVER_HEIGHT as u32).to_be_bytes());
+    ihdr.extend_from_slice(&(width as u32).to_be_bytes());
+    ihdr.extend_from_slice(&(height as u32).to_be_bytes());
     ihdr.push(8); // bit depth
     ihdr.push(6); // color type (RGBA)
     ihdr.push(0); // compression
     ihdr.push(0); // filter
     ihdr.push(0); // interlace
-    write_chunk(&mut out, b"IHDR", &ihdr);
+    write_chunk(out, b"IHDR", &ihdr);
+}
 
-    // IDAT（非圧縮deflate）
-    let mut raw = Vec::with_capacity(COVER_HEIGHT * (1 + COVER_WIDTH * 4));
-    for y in 0..COVER_HEIGHT {
+/// RGBAを画像データ（zlibラッパー + 非圧縮deflate）に変換
+fn zlib_image_data(rgba: &[u8], width: usize, height: usize) -> Vec<u8> {
+    let mut raw = Vec::with_capacity(height * (1 + width * 4));
+    for y in 0..height {
         raw.push(0); // filter type: none
-        let start = y * COVER_WIDTH * 4;
-        raw.extend_from_slice(&rgba[start..start + COVER_WIDTH * 4]);
+        let start = y * width * 4;
+        raw.extend_from_slice(&rgba[start..start + width * 4]);
     }
 
-    // zlibラッパー + 非圧縮ブロック
     let mut zlib = vec![0x78, 0x01]; // zlib header (no compression)
     let mut pos = 0;
     while pos < raw.len() {
@@ -82,57 +169,455 @@ pub fn encode_png(rgba: &[u8]) -> Vec<u8> {
         pos += block_size;
     }
     zlib.extend_from_slice(&adler32(&raw).to_be_bytes());
+    zlib
+}
 
-    write_chunk(&mut out, b"IDAT", &zlib);
+/// PNGチャンク長の上限（仕様上 2^31 - 1）
+pub(crate) const PNG_MAX_CHUNK_LEN: usize = 0x7FFF_FFFF;
 
-    // IEND
-    write_chunk(&mut out, b"IEND", &[]);
+/// 検証済みのPNGチャンク
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PngChunk<'a> {
+    /// チャンク先頭（長さフィールド）のオフセット
+    pub offset: usize,
+    pub chunk_type: [u8; 4],
+    pub data: &'a [u8],
+}
 
-    out
+impl PngChunk<'_> {
+    /// チャンク終端（CRCの直後）のオフセット
+    pub fn end(&self) -> usize {
+        self.offset + 12 + self.data.len()
+    }
 }
 
-/// PNGデータからIEND終端位置を探す（その後にペイロードがある）
-pub fn find_png_end(data: &[u8]) -> Result<usize, PinkError> {
-    if data.len() < 8 || data[0..8] != PNG_SIGNATURE {
-        return Err(PinkError::InvalidFormat);
+/// PNGのチャンクを先頭から順に読むイテレータ
+///
+/// CRCを検証し、最初のチャンクがIHDRであることを確認する。
+/// IENDを返した時点、またはエラーを返した時点で終了する。
+#[derive(Debug, Clone)]
+pub struct PngChunks<'a> {
+    data: &'a [u8],
+    pos: usize,
+    done: bool,
+}
+
+impl<'a> PngChunks<'a> {
+    /// PNGシグネチャを確認してイテレータを作成
+    pub fn new(data: &'a [u8]) -> Result<Self, PinkError> {
+        if data.len() < 8 || data[0..8] != PNG_SIGNATURE {
+            return Err(PinkError::InvalidFormat);
+        }
+        Ok(PngChunks {
+            data,
+            pos: 8,
+            done: false,
+        })
     }
 
-    let mut pos = 8;
-    while pos + 12 <= data.len() {
+    fn read_chunk(&self) -> Result<PngChunk<'a>, PinkError> {
+        let data = self.data;
+        let pos = self.pos;
+        if pos + 12 > data.len() {
+            return Err(PinkError::PngMissingIend);
+        }
+
         let len =
             u32::from_be_bytes([data[pos], data[pos + 1], data[pos + 2], data[pos + 3]]) as usize;
-        let chunk_type = &data[pos + 4..pos + 8];
-
         // チャンク全体: 4(len) + 4(type) + len(data) + 4(crc)
-        let chunk_end = pos + 12 + len;
-        if chunk_end > data.len() {
-            return Err(PinkError::InvalidFormat);
+        if len > PNG_MAX_CHUNK_LEN || len > data.len() - pos - 12 {
+            return Err(PinkError::PngChunkOverflow { offset: pos });
+        }
+
+        let crc_start = pos + 8 + len;
+        let stored_crc = u32::from_be_bytes([
+            data[crc_start],
+            data[crc_start + 1],
+            data[crc_start + 2],
+            data[crc_start + 3],
+        ]);
+        if crc32(&data[pos + 4..crc_start]) != stored_crc {
+            return Err(PinkError::PngCrcMismatch { offset: pos });
+        }
+
+        let mut chunk_type = [0u8; 4];
+        chunk_type.copy_from_slice(&data[pos + 4..pos + 8]);
+        if pos == 8 && &chunk_type != b"IHDR" {
+            return Err(PinkError::PngMissingIhdr);
+        }
+
+        Ok(PngChunk {
+            offset: pos,
+            chunk_type,
+            data: &data[pos + 8..crc_start],
+        })
+    }
+}
+
+impl<'a> Iterator for PngChunks<'a> {
+    type Item = Result<PngChunk<'a>, PinkError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.read_chunk() {
+            Ok(chunk) => {
+                self.pos = chunk.end();
+                self.done = &chunk.chunk_type == b"IEND";
+                Some(Ok(chunk))
+            }
+            Err(e) => {
+                self.done = true;
+                Some(Err(e))
+            }
         }
+    }
+}
+
+/// PNGデータからIEND終端位置を探す（その後にペイロードがある）
+pub fn find_png_end(data: &[u8]) -> Result<usize, PinkError> {
+    for chunk in PngChunks::new(data)? {
+        let chunk = chunk?;
+        if &chunk.chunk_type == b"IEND" {
+            return Ok(chunk.end());
+        }
+    }
+    Err(PinkError::PngMissingIend)
+}
+
+/// デコードしたPNG画像（常に8bit RGBA）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedImage {
+    pub width: u32,
+    pub height: u32,
+    pub rgba: Vec<u8>,
+}
 
-        if chunk_type == b"IEND" {
-            return Ok(chunk_end);
+/// PNGをRGBAにデコード
+///
+/// 対応形式: 8bit RGB / RGBA、インターレースなし、全フィルタタイプ。
+/// APNGの場合は既定画像（IDAT）を返す。IEND以降のデータは無視する。
+pub fn decode_png(data: &[u8]) -> Result<DecodedImage, PinkError> {
+    let mut ihdr: Option<&[u8]> = None;
+    let mut idat = Vec::new();
+    for chunk in PngChunks::new(data)? {
+        let chunk = chunk?;
+        match &chunk.chunk_type {
+            b"IHDR" => ihdr = Some(chunk.data),
+            b"IDAT" => idat.extend_from_slice(chunk.data),
+            b"IEND" => break,
+            _ => {}
         }
+    }
+
+    let ihdr = ihdr
+        .filter(|h| h.len() == 13)
+        .ok_or(PinkError::PngMissingIhdr)?;
+    let width = u32::from_be_bytes([ihdr[0], ihdr[1], ihdr[2], ihdr[3]]);
+    let height = u32::from_be_bytes([ihdr[4], ihdr[5], ihdr[6], ihdr[7]]);
+    let (bit_depth, color_type, interlace) = (ihdr[8], ihdr[9], ihdr[12]);
+
+    let channels = match color_type {
+        2 => 3, // RGB
+        6 => 4, // RGBA
+        _ => return Err(PinkError::UnsupportedPng),
+    };
+    if bit_depth != 8 || interlace != 0 || ihdr[10] != 0 || ihdr[11] != 0 {
+        return Err(PinkError::UnsupportedPng);
+    }
+    if width == 0 || height == 0 {
+        return Err(PinkError::InvalidCover);
+    }
 
-        pos = chunk_end;
+    let stride = (width as usize)
+        .checked_mul(channels)
+        .ok_or(PinkError::InvalidCover)?;
+    let raw_len = stride
+        .checked_add(1)
+        .and_then(|row| row.checked_mul(height as usize))
+        .ok_or(PinkError::InvalidCover)?;
+    let raw = zlib_decompress(&idat, raw_len)?;
+    if raw.len() != raw_len {
+        return Err(PinkError::CorruptImageData);
     }
 
-    Err(PinkError::InvalidFormat)
+    let pixels = unfilter(&raw, stride, height as usize, channels)?;
+    let rgba = if channels == 4 {
+        pixels
+    } else {
+        pixels
+            .chunks_exact(3)
+            .flat_map(|rgb| [rgb[0], rgb[1], rgb[2], 0xFF])
+            .collect()
+    };
+
+    Ok(DecodedImage {
+        width,
+        height,
+        rgba,
+    })
+}
+
+/// フィルタを外してピクセル列に戻す（None / Sub / Up / Average / Paeth）
+fn unfilter(raw: &[u8], stride: usize, height: usize, bpp: usize) -> Result<Vec<u8>, PinkError> {
+    let mut out = vec![0u8; stride * height];
+    for y in 0..height {
+        let filter = raw[y * (stride + 1)];
+        let line = &raw[y * (stride + 1) + 1..(y + 1) * (stride + 1)];
+        let (done, rest) = out.split_at_mut(y * stride);
+        let prev = if y > 0 {
+            &done[(y - 1) * stride..]
+        } else {
+            &[][..]
+        };
+        let cur = &mut rest[..stride];
+
+        for x in 0..stride {
+            let a = if x >= bpp { cur[x - bpp] } else { 0 };
+            let b = prev.get(x).copied().unwrap_or(0);
+            let c = if x >= bpp {
+                prev.get(x - bpp).copied().unwrap_or(0)
+            } else {
+                0
+            };
+            let predictor = match filter {
+                0 => 0,
+                1 => a,
+                2 => b,
+                3 => ((a as u16 + b as u16) / 2) as u8,
+                4 => paeth(a, b, c),
+                _ => return Err(PinkError::CorruptImageData),
+            };
+            cur[x] = line[x].wrapping_add(predictor);
+        }
+    }
+    Ok(out)
+}
+
+fn paeth(a: u8, b: u8, c: u8) -> u8 {
+    let p = a as i16 + b as i16 - c as i16;
+    let pa = (p - a as i16).abs();
+    let pb = (p - b as i16).abs();
+    let pc = (p - c as i16).abs();
+    if pa <= pb && pa <= pc {
+        a
+    } else if pb <= pc {
+        b
+    } else {
+        c
+    }
 }
 
 /// PINK-072フレームをPNK形式にエンコード
 /// 出力: [PNG(カバー画像)][PINK-072フレーム全体]
-pub fn encode_pnk(frame: &[u8]) -> Vec<u8> {
-    let cover = &frame[crate::constants::HEADER_LEN..crate::constants::HEADER_LEN + COVER_LEN];
-    let mut out = encode_png(cover);
+///
+/// 外部カバーのフレームは、ヘッダに記録された幅・高さのPNGになる。
+/// ヘッダを解析できないフレームや、カバーまで揃っていないフレームはエラーになる。
+pub fn encode_pnk(frame: &[u8]) -> Result<Vec<u8>, PinkError> {
+    encode_pnk_with_layout(frame, PnkLayout::Trailing)
+}
+
+/// レイアウトを指定してPINK-072フレームをPNK形式にエンコード
+///
+/// `PnkLayout::Chunk` では `PNK_CHUNK_MAX_LEN` ごとに複数の `pnKa` チャンクに分割する。
+pub fn encode_pnk_with_layout(frame: &[u8], layout: PnkLayout) -> Result<Vec<u8>, PinkError> {
+    let header = FrameHeader::parse(frame)?;
+    let cover = &frame[header.cover_range()];
+    let mut out = encode_png_rgba(cover, header.cover_width, header.cover_height);
+    match layout {
+        PnkLayout::Trailing => out.extend_from_slice(frame),
+        PnkLayout::Chunk => {
+            // IEND（常に12バイト）を外してチャンクを挿入し、付け直す
+            out.truncate(out.len() - 12);
+            for part in frame.chunks(PNK_CHUNK_MAX_LEN) {
+                write_chunk(&mut out, &PNK_CHUNK_TYPE, part);
+            }
+            write_chunk(&mut out, b"IEND", &[]);
+        }
+    }
+    Ok(out)
+}
+
+/// PINK-072フレームをアニメーションPNK形式にエンコード
+/// 出力: [APNG(シードから生成した動くカバー)][PINK-072フレーム全体]
+///
+/// カバーは `seed9` から生成されたものでなければならない（異なれば `CoverMismatch`）。
+/// 1フレーム目はフレーム内のカバーと同一。
+/// `frames` が0か `COVER_APNG_MAX_FRAMES` を超えれば `InvalidCover`。
+pub fn encode_pnk_animated(
+    frame: &[u8],
+    seed9: impl Into<Seed9>,
+    frames: u32,
+    delay_ms: u16,
+) -> Result<Vec<u8>, PinkError> {
+    let seed9 = seed9.into();
+    let header = FrameHeader::parse(frame)?;
+    if header.is_external_cover() {
+        return Err(PinkError::CoverMismatch);
+    }
+    verify_cover(&frame[header.cover_range()], &seed9)?;
+
+    let mut out = render_cover_apng(seed9, frames, delay_ms)?;
     out.extend_from_slice(frame);
-    out
+    Ok(out)
 }
 
 /// PNK形式からPINK-072フレームを抽出
-pub fn decode_pnk(data: &[u8]) -> Result<&[u8], PinkError> {
-    let png_end = find_png_end(data)?;
-    if png_end >= data.len() {
+///
+/// レイアウトは自動判定する。`pnKa` チャンクがあればその連結を、
+/// 無ければIEND以降を返す。マジック `PINK` で始まらず、旧形式のフレームとしても
+/// 認識できないデータは `InvalidFormat` になる。
+pub fn decode_pnk(data: &[u8]) -> Result<Cow<'_, [u8]>, PinkError> {
+    let (_, frame) = locate_frame(data)?;
+    if !frame.starts_with(&FRAME_MAGIC) && FrameHeader::sniff(&frame).is_none() {
         return Err(PinkError::InvalidFormat);
     }
-    Ok(&data[png_end..])
+    Ok(frame)
+}
+
+/// フレームの格納場所を判定して取り出す
+fn locate_frame(data: &[u8]) -> Result<(PnkLayout, Cow<'_, [u8]>), PinkError> {
+    // PngChunks はIENDで終わるか、エラーを返す
+    let mut png_end = 0;
+    let mut parts: Vec<&[u8]> = Vec::new();
+    for chunk in PngChunks::new(data)? {
+        let chunk = chunk?;
+        if chunk.chunk_type == PNK_CHUNK_TYPE {
+            parts.push(chunk.data);
+        }
+        png_end = chunk.end();
+    }
+
+    match parts.as_slice() {
+        [] if png_end < data.len() => Ok((PnkLayout::Trailing, Cow::Borrowed(&data[png_end..]))),
+        [] => Err(PinkError::InvalidFormat),
+        [single] => Ok((PnkLayout::Chunk, Cow::Borrowed(single))),
+        _ => Ok((PnkLayout::Chunk, Cow::Owned(parts.concat()))),
+    }
+}
+
+/// `pnk_info` / `detect_pnk` の結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PnkInfo {
+    pub layout: PnkLayout,
+    pub frame: FrameInfo,
+}
+
+/// PNKのレイアウトとフレームヘッダを調べる（ペイロードは展開しない）
+///
+/// PINK-072フレームが見つからなければ `None`。
+/// マジックの無い旧形式のフレームは、ヘッダの既定値が揃っている場合のみ認識する。
+pub fn pnk_info(data: &[u8]) -> Option<PnkInfo> {
+    let (layout, frame) = locate_frame(data).ok()?;
+    let header = FrameHeader::sniff(&frame)?;
+    Some(PnkInfo {
+        layout,
+        frame: header.info(),
+    })
+}
+
+/// データがPINK-072フレームを含むPNKか判定する
+pub fn is_pnk(data: &[u8]) -> bool {
+    pnk_info(data).is_some()
+}
+
+/// PNK形式からPINK-072フレームを抽出し、カバーがシード由来か検証する（厳格モード）
+///
+/// フレーム内のカバーと、表示されるPNG画像の両方を `seed9` から再生成した
+/// カバーと比較し、どちらかが異なれば `CoverMismatch` を返す。
+/// 表示画像はデコードして比較するため、再圧縮されたPNGやAPNGも受け付ける。
+/// 外部カバーのフレームは常に拒否する。
+pub fn decode_pnk_strict(data: &[u8], seed9: impl Into<Seed9>) -> Result<Cow<'_, [u8]>, PinkError> {
+    let frame = decode_pnk(data)?;
+    let header = FrameHeader::parse(&frame)?;
+    if header.is_external_cover() {
+        return Err(PinkError::CoverMismatch);
+    }
+
+    let cover = &frame[header.cover_range()];
+    verify_cover(cover, &seed9.into())?;
+    let image = decode_png(data)?;
+    if (image.width as usize, image.height as usize) != (COVER_WIDTH, COVER_HEIGHT)
+        || image.rgba != *cover
+    {
+        return Err(PinkError::CoverMismatch);
+    }
+    Ok(frame)
+}
+
+/// PNK形式から表示される画像（PNG部分）をデコード
+pub fn decode_pnk_image(data: &[u8]) -> Result<DecodedImage, PinkError> {
+    decode_png(data)
+}
+
+/// チャンク一覧の1項目（`inspect_pnk` 用）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PngChunkInfo {
+    pub offset: usize,
+    pub chunk_type: [u8; 4],
+    pub length: usize,
+}
+
+/// `inspect_pnk` の診断結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PnkInspection {
+    /// IENDまでに読めたチャンク
+    pub chunks: Vec<PngChunkInfo>,
+    /// PNG部分の解析エラー（正常なら `None`）
+    pub png_error: Option<PinkError>,
+    /// IEND終端のオフセット（IENDが見つからなければ `None`）
+    pub png_end: Option<usize>,
+    /// IEND以降のバイト数
+    pub trailing_len: usize,
+    /// フレーム（IEND以降または `pnKa` チャンク）のヘッダの解析結果
+    pub frame: Option<Result<FrameInfo, PinkError>>,
+}
+
+/// PNKファイルの構造を調べる（診断用）
+///
+/// 途中でエラーがあっても、そこまでに読めたチャンクを返す。
+pub fn inspect_pnk(data: &[u8]) -> PnkInspection {
+    let mut inspection = PnkInspection {
+        chunks: Vec::new(),
+        png_error: None,
+        png_end: None,
+        trailing_len: 0,
+        frame: None,
+    };
+
+    let chunks = match PngChunks::new(data) {
+        Ok(chunks) => chunks,
+        Err(e) => {
+            inspection.png_error = Some(e);
+            return inspection;
+        }
+    };
+
+    for chunk in chunks {
+        match chunk {
+            Ok(chunk) => {
+                inspection.chunks.push(PngChunkInfo {
+                    offset: chunk.offset,
+                    chunk_type: chunk.chunk_type,
+                    length: chunk.data.len(),
+                });
+                if &chunk.chunk_type == b"IEND" {
+                    inspection.png_end = Some(chunk.end());
+                }
+            }
+            Err(e) => inspection.png_error = Some(e),
+        }
+    }
+    if inspection.png_end.is_none() && inspection.png_error.is_none() {
+        inspection.png_error = Some(PinkError::PngMissingIend);
+    }
+
+    if let Some(png_end) = inspection.png_end {
+        inspection.trailing_len = data.len() - png_end;
+        if let Ok(frame) = decode_pnk(data) {
+            inspection.frame = Some(FrameHeader::parse(&frame).map(|h| h.info()));
+        }
+    }
+    inspection
 }
@@ -0,0 +1,162 @@
+//! Pythonバインディング（`python` フィーチャー、maturinでビルドする）
+//!
+//! 失敗は `pink072.PinkError`（`ValueError` の派生）になり、`code` 属性に
+//! `PinkError::code` と同じ識別子を持つ。ファイルの読み書きの失敗は `OSError`。
+
+use std::io;
+use std::path::PathBuf;
+
+use pyo3::create_exception;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
+
+use crate::core::{pink072_unwrap, pink072_wrap};
+use crate::file::{self, PAYLOAD_TYPE_FILE, PAYLOAD_TYPE_RAW, PAYLOAD_TYPE_ZIP};
+use crate::png;
+use crate::scan;
+use crate::seed::Seed9;
+
+create_exception!(
+    pink072,
+    PinkError,
+    PyValueError,
+    "PINK-072 error; `code` holds a stable identifier such as \"TRUNCATED_FRAME\"."
+);
+
+/// `PinkError` をPythonの例外にする
+fn pink_err(py: Python<'_>, e: &crate::error::PinkError) -> PyErr {
+    py_err(py, e.to_string(), e.code())
+}
+
+fn py_err(py: Python<'_>, message: String, code: &str) -> PyErr {
+    let err = PinkError::new_err(message);
+    // 作ったばかりの例外オブジェクトへの属性設定は失敗しない
+    let _ = err.value(py).setattr("code", code);
+    err
+}
+
+/// ファイル単位の関数の `io::Error` をPythonの例外にする
+///
+/// フレームの誤りは `PinkError`、それ以外の読み書きの失敗は `OSError` になる。
+fn io_err(py: Python<'_>, e: io::Error) -> PyErr {
+    let inner = e
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<crate::error::PinkError>());
+    match inner {
+        Some(inner) => pink_err(py, inner),
+        None if e.kind() == io::ErrorKind::InvalidData => {
+            py_err(py, e.to_string(), "INVALID_FORMAT")
+        }
+        None => e.into(),
+    }
+}
+
+/// シードを9バイトの `bytes` か文字列（16進・Base32・単語）から読む
+fn seed(py: Python<'_>, seed: &Bound<'_, PyAny>) -> PyResult<Seed9> {
+    let seed = if let Ok(bytes) = seed.cast::<PyBytes>() {
+        Seed9::try_from(bytes.as_bytes())
+    } else if let Ok(text) = seed.extract::<String>() {
+        text.parse()
+    } else {
+        return Err(py_err(
+            py,
+            "seed must be bytes or str".to_string(),
+            "INVALID_ARGUMENT",
+        ));
+    };
+    seed.map_err(|e| pink_err(py, &e))
+}
+
+/// ペイロードをフレームにする
+#[pyfunction]
+#[pyo3(signature = (payload, payload_type, seed9))]
+fn wrap<'py>(
+    py: Python<'py>,
+    payload: &[u8],
+    payload_type: u8,
+    seed9: &Bound<'py, PyAny>,
+) -> PyResult<Bound<'py, PyBytes>> {
+    let seed9 = seed(py, seed9)?;
+    let frame = pink072_wrap(payload, payload_type, seed9).map_err(|e| pink_err(py, &e))?;
+    Ok(PyBytes::new(py, &frame))
+}
+
+/// フレームから `(payload_type, payload)` を取り出す
+#[pyfunction]
+fn unwrap<'py>(py: Python<'py>, frame: &[u8]) -> PyResult<(u8, Bound<'py, PyBytes>)> {
+    let (payload_type, payload) = pink072_unwrap(frame).map_err(|e| pink_err(py, &e))?;
+    Ok((payload_type, PyBytes::new(py, &payload)))
+}
+
+/// フレームをPNK（PNG）にする
+#[pyfunction]
+fn encode_pnk<'py>(py: Python<'py>, frame: &[u8]) -> PyResult<Bound<'py, PyBytes>> {
+    let pnk = png::encode_pnk(frame).map_err(|e| pink_err(py, &e))?;
+    Ok(PyBytes::new(py, &pnk))
+}
+
+/// PNKからフレームを取り出す
+#[pyfunction]
+fn decode_pnk<'py>(py: Python<'py>, data: &[u8]) -> PyResult<Bound<'py, PyBytes>> {
+    let frame = png::decode_pnk(data).map_err(|e| pink_err(py, &e))?;
+    Ok(PyBytes::new(py, &frame))
+}
+
+/// 単一ファイルをPNKファイルにする
+#[pyfunction]
+fn encode_file(
+    py: Python<'_>,
+    input_path: PathBuf,
+    output_path: PathBuf,
+    seed9: &Bound<'_, PyAny>,
+) -> PyResult<()> {
+    let seed9 = seed(py, seed9)?;
+    py.detach(|| file::encode_file(&input_path, &output_path, seed9))
+        .map_err(|e| io_err(py, e))
+}
+
+/// PNKファイルを `output_dir` に展開し、書き出したファイル名の一覧を返す
+#[pyfunction]
+fn decode_auto(py: Python<'_>, input_path: PathBuf, output_dir: PathBuf) -> PyResult<Vec<String>> {
+    py.detach(|| file::decode_auto(&input_path, &output_dir))
+        .map_err(|e| io_err(py, e))
+}
+
+/// `root` 以下のPNKを探し、1件ごとの情報を `dict` のリストで返す
+#[pyfunction]
+#[pyo3(name = "scan")]
+fn scan_dir<'py>(py: Python<'py>, root: PathBuf) -> PyResult<Vec<Bound<'py, PyDict>>> {
+    let found: Vec<_> = py.detach(|| scan::scan(&root).collect());
+    found
+        .into_iter()
+        .map(|result| {
+            let entry = PyDict::new(py);
+            entry.set_item("path", result.path)?;
+            entry.set_item("format", result.format.extension())?;
+            entry.set_item("version", result.frame.version)?;
+            entry.set_item("payload_type", result.frame.payload_type)?;
+            entry.set_item("flags", result.frame.flags)?;
+            entry.set_item("payload_len", result.frame.payload_len)?;
+            entry.set_item("file_name", result.file_name)?;
+            entry.set_item("intact", result.intact)?;
+            Ok(entry)
+        })
+        .collect()
+}
+
+#[pymodule]
+fn pink072(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add("PinkError", m.py().get_type::<PinkError>())?;
+    m.add("PAYLOAD_TYPE_RAW", PAYLOAD_TYPE_RAW)?;
+    m.add("PAYLOAD_TYPE_FILE", PAYLOAD_TYPE_FILE)?;
+    m.add("PAYLOAD_TYPE_ZIP", PAYLOAD_TYPE_ZIP)?;
+    m.add_function(wrap_pyfunction!(wrap, m)?)?;
+    m.add_function(wrap_pyfunction!(unwrap, m)?)?;
+    m.add_function(wrap_pyfunction!(encode_pnk, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_pnk, m)?)?;
+    m.add_function(wrap_pyfunction!(encode_file, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_auto, m)?)?;
+    m.add_function(wrap_pyfunction!(scan_dir, m)?)?;
+    Ok(())
+}
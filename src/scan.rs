@@ -0,0 +1,112 @@
+//! ディレクトリ以下のPNKを探して内容を報告する
+
+use std::fs::{self, File, ReadDir};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::carrier::{decode_carrier, CarrierFormat};
+use crate::core::{FrameHeader, FrameInfo};
+use crate::file::{parse_file_payload, PAYLOAD_TYPE_FILE};
+
+/// `scan` が見つけたPNK1件分の情報
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScanResult {
+    pub path: PathBuf,
+    /// キャリアの画像形式（拡張子ではなく中身から判定）
+    pub format: CarrierFormat,
+    /// フレームヘッダ（ペイロードタイプ・ペイロード長など）
+    pub frame: FrameInfo,
+    /// `PAYLOAD_TYPE_FILE` の場合に格納されているファイル名
+    pub file_name: Option<String>,
+    /// カバーとペイロード（署名があればそれも）がヘッダの示す長さまで揃っているか
+    pub intact: bool,
+}
+
+/// `root` 以下のファイルを再帰的に調べ、PINK-072フレームを含むものを順に返す
+///
+/// 拡張子に関係なく、対応するキャリア形式（PNG・JPEG・GIF・BMP・WebP）の
+/// シグネチャで始まるファイルをすべて調べる。ペイロードは展開しない。
+/// 読めないファイルやディレクトリは読み飛ばし、シンボリックリンクは辿らない。
+/// `root` がファイルならそのファイルだけを調べる。
+pub fn scan(root: impl AsRef<Path>) -> impl Iterator<Item = ScanResult> {
+    let root = root.as_ref();
+    let mut walk = Walk {
+        dirs: Vec::new(),
+        root_file: None,
+    };
+    match fs::symlink_metadata(root) {
+        Ok(meta) if meta.is_dir() => walk.dirs.extend(fs::read_dir(root).ok()),
+        Ok(meta) if meta.is_file() => walk.root_file = Some(root.to_path_buf()),
+        _ => {}
+    }
+    walk.filter_map(|path| scan_file(&path))
+}
+
+/// ディレクトリを深さ優先で辿り、通常ファイルのパスを返す
+struct Walk {
+    dirs: Vec<ReadDir>,
+    root_file: Option<PathBuf>,
+}
+
+impl Iterator for Walk {
+    type Item = PathBuf;
+
+    fn next(&mut self) -> Option<PathBuf> {
+        if let Some(path) = self.root_file.take() {
+            return Some(path);
+        }
+        while let Some(dir) = self.dirs.last_mut() {
+            let Some(entry) = dir.next() else {
+                self.dirs.pop();
+                continue;
+            };
+            let Ok(entry) = entry else { continue };
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+            if file_type.is_dir() {
+                self.dirs.extend(fs::read_dir(entry.path()).ok());
+            } else if file_type.is_file() {
+                return Some(entry.path());
+            }
+        }
+        None
+    }
+}
+
+/// 1ファイルを調べる（PINK-072フレームが無ければ `None`）
+fn scan_file(path: &Path) -> Option<ScanResult> {
+    let mut file = File::open(path).ok()?;
+
+    // 画像でないファイルは先頭だけ読んで判定する
+    let mut data = Vec::new();
+    (&mut file).take(12).read_to_end(&mut data).ok()?;
+    CarrierFormat::detect(&data)?;
+    file.read_to_end(&mut data).ok()?;
+
+    let (format, frame) = decode_carrier(&data).ok()?;
+    let header = FrameHeader::read(&frame).ok()?;
+    let parsed = FrameHeader::parse(&frame).ok();
+    let intact = parsed
+        .as_ref()
+        .and_then(|header| header.frame_len().ok())
+        .is_some_and(|len| len <= frame.len());
+    let payload = parsed.and_then(|header| header.payload_range().ok());
+
+    // ファイル名はペイロード先頭にあるので、途中で切れていても読めれば返す
+    let file_name = match payload {
+        Some(range) if header.payload_type == PAYLOAD_TYPE_FILE => {
+            let available = &frame[range.start..range.end.min(frame.len())];
+            parse_file_payload(available).ok().map(|(name, _)| name)
+        }
+        _ => None,
+    };
+
+    Some(ScanResult {
+        path: path.to_path_buf(),
+        format,
+        frame: header.info(),
+        file_name,
+        intact,
+    })
+}
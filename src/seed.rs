@@ -0,0 +1,275 @@
+//! 9バイトのシード（Seed9）と、その生成・表記ユーティリティ
+
+#[cfg(feature = "alloc")]
+use alloc::{format, string::String, vec::Vec};
+use core::fmt;
+use core::str::FromStr;
+
+use pbkdf2::pbkdf2_hmac;
+#[cfg(feature = "alloc")]
+use sha2::Digest;
+use sha2::Sha256;
+
+use crate::error::PinkError;
+
+/// パスフレーズからの導出に使う固定ソルト
+const PASSPHRASE_SALT: &[u8] = b"PINK-072 seed9 v1";
+/// PBKDF2-HMAC-SHA256 の反復回数
+const PASSPHRASE_ROUNDS: u32 = 100_000;
+
+/// フィンガープリント計算用のドメイン分離タグ
+#[cfg(feature = "alloc")]
+const FINGERPRINT_TAG: &[u8] = b"PINK-072 fingerprint v1";
+
+/// RFC 4648 Base32 アルファベット
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+/// 9バイト（72bit）をBase32で表した文字数
+const BASE32_LEN: usize = 15;
+
+/// カバー生成に使う9バイトのシード
+///
+/// 長さは型で保証されるため、生のスライスから変換する場合のみ
+/// `SeedLength` エラーが発生しうる。
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Seed9([u8; 9]);
+
+impl Seed9 {
+    pub const fn new(bytes: [u8; 9]) -> Self {
+        Seed9(bytes)
+    }
+
+    pub fn as_bytes(&self) -> &[u8; 9] {
+        &self.0
+    }
+
+    /// パスフレーズからシードを導出（PBKDF2-HMAC-SHA256）
+    ///
+    /// 同じパスフレーズからは常に同じシードが得られる。
+    pub fn from_passphrase(passphrase: &str) -> Self {
+        let mut out = [0u8; 9];
+        pbkdf2_hmac::<Sha256>(
+            passphrase.as_bytes(),
+            PASSPHRASE_SALT,
+            PASSPHRASE_ROUNDS,
+            &mut out,
+        );
+        Seed9(out)
+    }
+
+    /// OSの乱数源からシードを生成
+    #[cfg(feature = "rand")]
+    pub fn random() -> Result<Self, PinkError> {
+        let mut out = [0u8; 9];
+        getrandom::getrandom(&mut out).map_err(|_| PinkError::Entropy)?;
+        Ok(Seed9(out))
+    }
+
+    /// Base32（RFC 4648、パディングなし）で表記
+    #[cfg(feature = "alloc")]
+    pub fn to_base32(&self) -> String {
+        base32_encode(&self.0)
+    }
+
+    /// シードを明かさずに照合するための短いフィンガープリント（例: `ABCD-EFGH`）
+    ///
+    /// シードのSHA-256から導出した40bitをBase32で表したもの。
+    /// 互いのフィンガープリントが一致すれば同じシードを共有している。
+    #[cfg(feature = "alloc")]
+    pub fn fingerprint(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(FINGERPRINT_TAG);
+        hasher.update(self.0);
+        let digest = hasher.finalize();
+        let code = base32_encode(&digest[..5]);
+        format!("{}-{}", &code[..4], &code[4..])
+    }
+
+    /// 読み上げやすい単語列で表記（1バイト1単語、`-` 区切り）
+    #[cfg(feature = "alloc")]
+    pub fn to_words(&self) -> String {
+        self.0
+            .iter()
+            .map(|&b| WORDS[b as usize])
+            .collect::<Vec<_>>()
+            .join("-")
+    }
+
+    /// 単語列からシードを復元（`-` または空白区切り、大文字小文字は区別しない）
+    pub fn from_words(s: &str) -> Result<Self, PinkError> {
+        let mut out = [0u8; 9];
+        let mut count = 0;
+        for word in s.split(|c: char| c == '-' || c.is_whitespace()) {
+            if word.is_empty() {
+                continue;
+            }
+            if count == out.len() {
+                return Err(PinkError::InvalidSeed);
+            }
+            // 単語表は小文字なので、小文字にしながら比較する（割り当て不要）
+            let index = WORDS
+                .binary_search_by(|candidate| {
+                    candidate
+                        .bytes()
+                        .cmp(word.bytes().map(|b| b.to_ascii_lowercase()))
+                })
+                .map_err(|_| PinkError::InvalidSeed)?;
+            out[count] = index as u8;
+            count += 1;
+        }
+        if count != out.len() {
+            return Err(PinkError::InvalidSeed);
+        }
+        Ok(Seed9(out))
+    }
+
+    fn from_hex(s: &str) -> Option<Self> {
+        if s.len() != 18 || !s.is_ascii() {
+            return None;
+        }
+        let mut out = [0u8; 9];
+        for (i, byte) in out.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[i * 2..i * 2 + 2], 16).ok()?;
+        }
+        Some(Seed9(out))
+    }
+
+    fn from_base32(s: &str) -> Option<Self> {
+        if s.len() != BASE32_LEN {
+            return None;
+        }
+        let mut out = [0u8; 9];
+        let mut buffer = 0u32;
+        let mut bits = 0;
+        let mut pos = 0;
+        for c in s.bytes() {
+            let value = BASE32_ALPHABET
+                .iter()
+                .position(|&a| a == c.to_ascii_uppercase())? as u32;
+            buffer = (buffer << 5) | value;
+            bits += 5;
+            if bits >= 8 {
+                bits -= 8;
+                out[pos] = (buffer >> bits) as u8;
+                pos += 1;
+            }
+        }
+        // 余りのビットは0でなければならない
+        if buffer & ((1 << bits) - 1) != 0 {
+            return None;
+        }
+        Some(Seed9(out))
+    }
+}
+
+/// RFC 4648 Base32 エンコード（パディングなし）
+#[cfg(feature = "alloc")]
+fn base32_encode(data: &[u8]) -> String {
+    let mut out = String::with_capacity((data.len() * 8).div_ceil(5));
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for &byte in data {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 31) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+    }
+    out
+}
+
+impl From<[u8; 9]> for Seed9 {
+    fn from(bytes: [u8; 9]) -> Self {
+        Seed9(bytes)
+    }
+}
+
+impl From<&[u8; 9]> for Seed9 {
+    fn from(bytes: &[u8; 9]) -> Self {
+        Seed9(*bytes)
+    }
+}
+
+impl From<&Seed9> for Seed9 {
+    fn from(seed: &Seed9) -> Self {
+        *seed
+    }
+}
+
+impl TryFrom<&[u8]> for Seed9 {
+    type Error = PinkError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, PinkError> {
+        <[u8; 9]>::try_from(bytes)
+            .map(Seed9)
+            .map_err(|_| PinkError::SeedLength)
+    }
+}
+
+impl AsRef<[u8]> for Seed9 {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// 16進数（小文字18文字）で表示
+impl fmt::Display for Seed9 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in &self.0 {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+/// シードは秘密情報なのでログに出さない
+impl fmt::Debug for Seed9 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Seed9(..)")
+    }
+}
+
+/// 16進数（18文字）、Base32（15文字）、単語列（9単語）のいずれかを受け付ける
+impl FromStr for Seed9 {
+    type Err = PinkError;
+
+    fn from_str(s: &str) -> Result<Self, PinkError> {
+        let s = s.trim();
+        Seed9::from_hex(s)
+            .or_else(|| Seed9::from_base32(s))
+            .map_or_else(|| Seed9::from_words(s), Ok)
+    }
+}
+
+/// 単語表記に使う256語（アルファベット順、インデックスがバイト値）
+const WORDS: [&str; 256] = [
+    "acorn", "acre", "aloe", "amber", "anchor", "angel", "apple", "april", "arch", "arrow",
+    "aspen", "atlas", "autumn", "bacon", "badge", "bagel", "bamboo", "banjo", "barn", "basil",
+    "beach", "bean", "bear", "berry", "birch", "bison", "blade", "bloom", "blush", "bonnet",
+    "border", "brave", "bread", "breeze", "brick", "bridge", "brook", "brush", "bubble", "bucket",
+    "bunny", "butter", "cabin", "cactus", "cake", "camel", "candle", "canoe", "canyon", "cape",
+    "carrot", "castle", "cedar", "cello", "chalk", "cherry", "chess", "chick", "cider", "cinema",
+    "circle", "citrus", "clay", "cliff", "clock", "cloud", "clover", "cobalt", "cocoa", "comet",
+    "coral", "cotton", "cougar", "cradle", "crane", "crayon", "creek", "crown", "cupid", "cycle",
+    "daisy", "dance", "dawn", "delta", "denim", "desert", "dew", "diamond", "dolphin", "donut",
+    "dove", "dragon", "dream", "drum", "dune", "eagle", "echo", "elbow", "elder", "ember",
+    "emerald", "engine", "fable", "falcon", "fawn", "feather", "fern", "fiddle", "fig", "flame",
+    "flute", "fog", "forest", "fossil", "fox", "frost", "fudge", "galaxy", "garden", "garnet",
+    "gecko", "gem", "ginger", "glacier", "globe", "grape", "gravel", "guitar", "harbor", "harp",
+    "hazel", "heart", "hedge", "heron", "hill", "honey", "iris", "island", "ivory", "jade", "jam",
+    "jasmine", "jelly", "jewel", "jungle", "kayak", "kettle", "kite", "kiwi", "koala", "ladder",
+    "lagoon", "lake", "lantern", "lark", "lava", "lemon", "lilac", "lily", "lime", "linen",
+    "lotus", "lunar", "magnet", "mango", "maple", "marble", "meadow", "melon", "mint", "mirror",
+    "mist", "moon", "moss", "muffin", "nectar", "nest", "noodle", "oasis", "ocean", "olive",
+    "onion", "opal", "orbit", "orchid", "otter", "owl", "paddle", "palm", "panda", "paper",
+    "parrot", "peach", "pearl", "pebble", "pepper", "petal", "piano", "pine", "pixel", "plum",
+    "pocket", "pony", "poppy", "prism", "pumpkin", "puzzle", "quail", "quartz", "quill", "rabbit",
+    "radar", "rain", "raven", "reef", "ribbon", "river", "robin", "rocket", "rose", "ruby",
+    "saddle", "sage", "salmon", "sand", "satin", "scarf", "shell", "silk", "silver", "sky",
+    "sloth", "snow", "sonnet", "spark", "sparrow", "spice", "spruce", "star", "stone", "sugar",
+    "summit", "sun", "swan", "tango", "tea", "thunder", "tiger", "topaz", "tulip", "velvet",
+    "violet", "walnut", "willow", "wind", "zebra",
+];
@@ -0,0 +1,189 @@
+//! Ed25519によるフレームの署名と検証
+//!
+//! 署名はペイロードの後ろのトレーラ `[鍵ID 8B][署名 64B]` に格納する。
+//! 署名対象はヘッダ（`FLAG_SIGNED` を立てた状態）からペイロード末尾までの全体。
+
+#[cfg(feature = "alloc")]
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::fmt;
+
+use ed25519_dalek::{Signature, VerifyingKey};
+#[cfg(feature = "alloc")]
+use ed25519_dalek::{Signer, SigningKey, Verifier};
+use sha2::{Digest, Sha256};
+
+use crate::constants::SIGNATURE_TRAILER_LEN;
+#[cfg(feature = "alloc")]
+use crate::constants::{FLAG_SIGNED, FRAME_VERSION};
+use crate::core::FrameHeader;
+use crate::error::PinkError;
+
+/// 署名する内容の前に付けるドメイン分離用の文字列
+#[cfg(feature = "alloc")]
+const SIGNATURE_CONTEXT: &[u8] = b"PINK-072 signature v1";
+
+/// 公開鍵の識別子（公開鍵のSHA-256の先頭8バイト）
+///
+/// 署名の鍵（Ed25519）と暗号化の受信者（X25519）の両方に使う。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct KeyId(pub [u8; 8]);
+
+impl KeyId {
+    /// 署名用の公開鍵の鍵ID
+    pub fn of(verifying_key: &VerifyingKey) -> Self {
+        KeyId::from_public_bytes(verifying_key.as_bytes())
+    }
+
+    /// 暗号化の受信者の公開鍵の鍵ID
+    pub fn of_recipient(public_key: &x25519_dalek::PublicKey) -> Self {
+        KeyId::from_public_bytes(public_key.as_bytes())
+    }
+
+    fn from_public_bytes(bytes: &[u8; 32]) -> Self {
+        let digest = Sha256::digest(bytes);
+        let mut id = [0u8; 8];
+        id.copy_from_slice(&digest[..8]);
+        KeyId(id)
+    }
+}
+
+impl fmt::Display for KeyId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+/// 信頼する公開鍵の一覧（鍵IDで引く）
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Default)]
+pub struct Keyring {
+    keys: BTreeMap<KeyId, VerifyingKey>,
+}
+
+#[cfg(feature = "alloc")]
+impl Keyring {
+    pub fn new() -> Self {
+        Keyring::default()
+    }
+
+    /// 公開鍵を追加し、その鍵IDを返す
+    pub fn insert(&mut self, verifying_key: VerifyingKey) -> KeyId {
+        let key_id = KeyId::of(&verifying_key);
+        self.keys.insert(key_id, verifying_key);
+        key_id
+    }
+
+    pub fn get(&self, key_id: &KeyId) -> Option<&VerifyingKey> {
+        self.keys.get(key_id)
+    }
+
+    pub fn remove(&mut self, key_id: &KeyId) -> Option<VerifyingKey> {
+        self.keys.remove(key_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl FromIterator<VerifyingKey> for Keyring {
+    fn from_iter<I: IntoIterator<Item = VerifyingKey>>(iter: I) -> Self {
+        let mut keyring = Keyring::new();
+        for verifying_key in iter {
+            keyring.insert(verifying_key);
+        }
+        keyring
+    }
+}
+
+/// フレームに署名する
+///
+/// ヘッダに `FLAG_SIGNED` を立て、ペイロードの後ろに署名トレーラを付けたフレームを返す。
+/// すでに署名済みのフレームは古い署名を外して署名し直す。
+/// マジックの無い旧形式のフレームには署名できない（`UnsupportedVersion`）。
+#[cfg(feature = "alloc")]
+pub fn sign_frame(frame: &[u8], signing_key: &SigningKey) -> Result<Vec<u8>, PinkError> {
+    let header = FrameHeader::parse(frame)?;
+    if header.version != FRAME_VERSION {
+        return Err(PinkError::UnsupportedVersion {
+            version: header.version,
+        });
+    }
+    let payload_end = header.payload_range()?.end;
+    if frame.len() < payload_end {
+        return Err(PinkError::TruncatedFrame);
+    }
+
+    let mut signed = Vec::with_capacity(payload_end + SIGNATURE_TRAILER_LEN);
+    signed.extend_from_slice(&frame[..payload_end]);
+    signed[7] |= FLAG_SIGNED;
+
+    let signature = signing_key.sign(&signed_message(&signed));
+    signed.extend_from_slice(&KeyId::of(&signing_key.verifying_key()).0);
+    signed.extend_from_slice(&signature.to_bytes());
+    Ok(signed)
+}
+
+/// 署名済みフレームの鍵IDを返す（署名を検証せずに、誰の署名かだけ調べる）
+///
+/// 署名の無いフレームは `None`。
+pub fn frame_key_id(frame: &[u8]) -> Result<Option<KeyId>, PinkError> {
+    let header = FrameHeader::parse(frame)?;
+    if !header.is_signed() {
+        return Ok(None);
+    }
+    let (key_id, _) = read_trailer(frame, header.payload_range()?.end)?;
+    Ok(Some(key_id))
+}
+
+/// フレームの署名を `keyring` の鍵で検証し、署名した鍵のIDを返す
+///
+/// 署名が無ければ `Unsigned`、鍵IDが `keyring` に無ければ `UntrustedKey`、
+/// 内容が改変されていれば `BadSignature` になる。
+#[cfg(feature = "alloc")]
+pub fn verify_frame(frame: &[u8], keyring: &Keyring) -> Result<KeyId, PinkError> {
+    let header = FrameHeader::parse(frame)?;
+    if !header.is_signed() {
+        return Err(PinkError::Unsigned);
+    }
+    let payload_end = header.payload_range()?.end;
+    let (key_id, signature) = read_trailer(frame, payload_end)?;
+    let verifying_key = keyring
+        .get(&key_id)
+        .ok_or(PinkError::UntrustedKey { key_id })?;
+
+    verifying_key
+        .verify(&signed_message(&frame[..payload_end]), &signature)
+        .map_err(|_| PinkError::BadSignature)?;
+    Ok(key_id)
+}
+
+/// 署名トレーラ（鍵IDと署名）を読む
+fn read_trailer(frame: &[u8], payload_end: usize) -> Result<(KeyId, Signature), PinkError> {
+    let trailer = payload_end
+        .checked_add(SIGNATURE_TRAILER_LEN)
+        .and_then(|end| frame.get(payload_end..end))
+        .ok_or(PinkError::TruncatedFrame)?;
+    let mut key_id = [0u8; 8];
+    key_id.copy_from_slice(&trailer[..8]);
+    let signature = Signature::from_slice(&trailer[8..]).map_err(|_| PinkError::BadSignature)?;
+    Ok((KeyId(key_id), signature))
+}
+
+/// 署名する内容: コンテキスト文字列 + 署名対象全体のSHA-256
+///
+/// 大きなペイロードを連結してコピーしないよう、ハッシュに署名する。
+#[cfg(feature = "alloc")]
+fn signed_message(signed_part: &[u8]) -> Vec<u8> {
+    let mut message = SIGNATURE_CONTEXT.to_vec();
+    message.extend_from_slice(&Sha256::digest(signed_part));
+    message
+}
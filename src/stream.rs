@@ -0,0 +1,379 @@
+//! PNKを少しずつ読むプッシュ型のデコーダ
+//!
+//! `fetch` のストリームやソケットから届いた断片を順に `feed` すると、PNGのチャンクを
+//! 辿りながらフレームのヘッダ・カバー・ペイロードをイベントとして返す。
+//! 対応するのはPNGのキャリア（IENDの後ろ・`pnKa` チャンクの両レイアウト）。
+
+use alloc::vec::Vec;
+use core::mem;
+
+use crate::constants::{FRAME_MAGIC, HEADER_LEN, SIGNATURE_TRAILER_LEN};
+use crate::core::{FrameHeader, FrameInfo};
+use crate::error::PinkError;
+use crate::png::{crc32_update, PNG_MAX_CHUNK_LEN, PNG_SIGNATURE, PNK_CHUNK_TYPE};
+
+/// `PnkDecoder::feed` が返すイベント
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PnkEvent {
+    /// フレームヘッダを読んだ
+    Header(FrameInfo),
+    /// カバー画像（RGBA）を読んだ
+    CoverReady {
+        width: u32,
+        height: u32,
+        rgba: Vec<u8>,
+    },
+    /// ペイロードの続き（暗号化されたフレームでは暗号文）
+    PayloadChunk(Vec<u8>),
+    /// フレームの終わりまで読んだ（署名トレーラは読み飛ばす）
+    Done,
+}
+
+/// プッシュ型のPNKデコーダ
+///
+/// 受け取った断片を `feed` に順に渡し、最後に `finish` でフレームが揃ったか確認する。
+/// `pnKa` チャンクのデータはCRCを確認する前にイベントとして返す
+/// （CRCが一致しなければ、そのチャンクの終わりでエラーになる）。
+/// 一度エラーを返すと、以降の `feed` は同じエラーを返す。
+#[derive(Debug, Default)]
+pub struct PnkDecoder {
+    png: PngState,
+    frame: FrameParser,
+    /// 固定長の欄（シグネチャ・チャンクヘッダ・CRC）の読みかけ
+    pending: Vec<u8>,
+    /// これまでに読んだバイト数
+    offset: usize,
+    /// 読んでいるチャンクの先頭オフセット
+    chunk_offset: usize,
+    /// `pnKa` チャンクがあった（チャンクレイアウト）
+    in_chunks: bool,
+    error: Option<PinkError>,
+}
+
+#[derive(Debug, Default)]
+enum PngState {
+    #[default]
+    Signature,
+    ChunkHeader,
+    ChunkData {
+        chunk_type: [u8; 4],
+        remaining: usize,
+        crc: u32,
+    },
+    ChunkCrc {
+        chunk_type: [u8; 4],
+        crc: u32,
+    },
+    /// IENDの後ろ（IEND後ろのレイアウトならフレームが続く）
+    AfterIend,
+}
+
+impl PnkDecoder {
+    pub fn new() -> Self {
+        PnkDecoder::default()
+    }
+
+    /// 次の断片を読み、新たに分かったことをイベントとして返す
+    pub fn feed(&mut self, data: &[u8]) -> Result<Vec<PnkEvent>, PinkError> {
+        if let Some(e) = &self.error {
+            return Err(e.clone());
+        }
+        let mut events = Vec::new();
+        let mut rest = data;
+        while !rest.is_empty() {
+            match self.step(rest, &mut events) {
+                Ok(used) => {
+                    rest = &rest[used..];
+                    self.offset += used;
+                }
+                Err(e) => {
+                    self.error = Some(e.clone());
+                    return Err(e);
+                }
+            }
+        }
+        Ok(events)
+    }
+
+    /// フレームの終わりまで読んだか
+    pub fn is_done(&self) -> bool {
+        self.frame.is_done()
+    }
+
+    /// 入力の終わりで呼び、フレームが揃っていなければエラーを返す
+    ///
+    /// IENDに届いていなければ `PngMissingIend`、IENDの後ろに何も無ければ
+    /// `InvalidFormat`、フレームが途中で終わっていれば `TruncatedFrame`。
+    pub fn finish(&self) -> Result<(), PinkError> {
+        if let Some(e) = &self.error {
+            return Err(e.clone());
+        }
+        if self.frame.is_done() {
+            return Ok(());
+        }
+        match self.png {
+            PngState::AfterIend if self.frame.is_empty() => Err(PinkError::InvalidFormat),
+            PngState::AfterIend => Err(PinkError::TruncatedFrame),
+            _ => Err(PinkError::PngMissingIend),
+        }
+    }
+
+    /// 今の状態で `data` の先頭を読み、使ったバイト数を返す
+    fn step(&mut self, data: &[u8], events: &mut Vec<PnkEvent>) -> Result<usize, PinkError> {
+        match &mut self.png {
+            PngState::Signature => {
+                let used = fill(&mut self.pending, data, PNG_SIGNATURE.len());
+                if self.pending.len() == PNG_SIGNATURE.len() {
+                    if self.pending != PNG_SIGNATURE {
+                        return Err(PinkError::InvalidFormat);
+                    }
+                    self.pending.clear();
+                    self.png = PngState::ChunkHeader;
+                }
+                Ok(used)
+            }
+            PngState::ChunkHeader => {
+                if self.pending.is_empty() {
+                    self.chunk_offset = self.offset;
+                }
+                let used = fill(&mut self.pending, data, 8);
+                if self.pending.len() < 8 {
+                    return Ok(used);
+                }
+
+                let len = u32::from_be_bytes([
+                    self.pending[0],
+                    self.pending[1],
+                    self.pending[2],
+                    self.pending[3],
+                ]) as usize;
+                let mut chunk_type = [0u8; 4];
+                chunk_type.copy_from_slice(&self.pending[4..8]);
+                self.pending.clear();
+
+                if len > PNG_MAX_CHUNK_LEN {
+                    return Err(PinkError::PngChunkOverflow {
+                        offset: self.chunk_offset,
+                    });
+                }
+                if self.chunk_offset == PNG_SIGNATURE.len() && &chunk_type != b"IHDR" {
+                    return Err(PinkError::PngMissingIhdr);
+                }
+                if chunk_type == PNK_CHUNK_TYPE {
+                    self.in_chunks = true;
+                }
+                self.png = PngState::ChunkData {
+                    chunk_type,
+                    remaining: len,
+                    crc: crc32_update(0xFFFF_FFFF, &chunk_type),
+                };
+                Ok(used)
+            }
+            PngState::ChunkData {
+                chunk_type,
+                remaining,
+                crc,
+            } => {
+                let used = (*remaining).min(data.len());
+                let part = &data[..used];
+                *crc = crc32_update(*crc, part);
+                *remaining -= used;
+                let (chunk_type, done, crc) = (*chunk_type, *remaining == 0, *crc);
+
+                if chunk_type == PNK_CHUNK_TYPE {
+                    // フレームの後ろの余りは読み捨てる
+                    self.frame.push(part, events)?;
+                }
+                if done {
+                    self.png = PngState::ChunkCrc { chunk_type, crc };
+                }
+                Ok(used)
+            }
+            PngState::ChunkCrc { chunk_type, crc } => {
+                let (chunk_type, crc) = (*chunk_type, *crc);
+                let used = fill(&mut self.pending, data, 4);
+                if self.pending.len() < 4 {
+                    return Ok(used);
+                }
+                let stored = u32::from_be_bytes([
+                    self.pending[0],
+                    self.pending[1],
+                    self.pending[2],
+                    self.pending[3],
+                ]);
+                self.pending.clear();
+                if !crc != stored {
+                    return Err(PinkError::PngCrcMismatch {
+                        offset: self.chunk_offset,
+                    });
+                }
+
+                if &chunk_type == b"IEND" {
+                    if self.in_chunks && !self.frame.is_done() {
+                        return Err(PinkError::TruncatedFrame);
+                    }
+                    self.png = PngState::AfterIend;
+                } else {
+                    self.png = PngState::ChunkHeader;
+                }
+                Ok(used)
+            }
+            PngState::AfterIend => {
+                if !self.in_chunks {
+                    // フレームの後ろに余りがあっても読み捨てる
+                    self.frame.push(data, events)?;
+                }
+                Ok(data.len())
+            }
+        }
+    }
+}
+
+/// フレーム部分を読む状態機械
+#[derive(Debug, Default)]
+struct FrameParser {
+    state: FrameState,
+    /// ヘッダ・カバーの読みかけ
+    buf: Vec<u8>,
+    info: Option<FrameInfo>,
+    cover_len: usize,
+    payload_len: usize,
+    trailer_len: usize,
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+enum FrameState {
+    #[default]
+    Header,
+    Extension {
+        remaining: usize,
+    },
+    Cover,
+    Payload {
+        remaining: usize,
+    },
+    Trailer {
+        remaining: usize,
+    },
+    Done,
+}
+
+impl FrameParser {
+    fn is_done(&self) -> bool {
+        matches!(self.state, FrameState::Done)
+    }
+
+    /// まだ1バイトも読んでいない
+    fn is_empty(&self) -> bool {
+        matches!(self.state, FrameState::Header) && self.buf.is_empty()
+    }
+
+    /// `data` を読み、使ったバイト数を返す（フレームの終わりで止まる）
+    fn push(&mut self, data: &[u8], events: &mut Vec<PnkEvent>) -> Result<usize, PinkError> {
+        let mut used = 0;
+        loop {
+            let rest = &data[used..];
+            match self.state {
+                FrameState::Header => {
+                    used += fill(&mut self.buf, rest, HEADER_LEN);
+                    if self.buf.len() < HEADER_LEN {
+                        break;
+                    }
+                    self.read_header(events)?;
+                }
+                FrameState::Extension { remaining } => {
+                    let n = remaining.min(rest.len());
+                    used += n;
+                    if remaining > n {
+                        self.state = FrameState::Extension {
+                            remaining: remaining - n,
+                        };
+                        break;
+                    }
+                    self.state = FrameState::Cover;
+                }
+                FrameState::Cover => {
+                    used += fill(&mut self.buf, rest, self.cover_len);
+                    if self.buf.len() < self.cover_len {
+                        break;
+                    }
+                    let info = self.info.expect("header is read before the cover");
+                    events.push(PnkEvent::CoverReady {
+                        width: info.cover_width,
+                        height: info.cover_height,
+                        rgba: mem::take(&mut self.buf),
+                    });
+                    self.state = FrameState::Payload {
+                        remaining: self.payload_len,
+                    };
+                }
+                FrameState::Payload { remaining } => {
+                    let n = remaining.min(rest.len());
+                    if n > 0 {
+                        events.push(PnkEvent::PayloadChunk(rest[..n].to_vec()));
+                        used += n;
+                    }
+                    if remaining > n {
+                        self.state = FrameState::Payload {
+                            remaining: remaining - n,
+                        };
+                        break;
+                    }
+                    self.state = FrameState::Trailer {
+                        remaining: self.trailer_len,
+                    };
+                }
+                FrameState::Trailer { remaining } => {
+                    let n = remaining.min(rest.len());
+                    used += n;
+                    if remaining > n {
+                        self.state = FrameState::Trailer {
+                            remaining: remaining - n,
+                        };
+                        break;
+                    }
+                    self.state = FrameState::Done;
+                    events.push(PnkEvent::Done);
+                }
+                FrameState::Done => break,
+            }
+        }
+        Ok(used)
+    }
+
+    /// ヘッダを解析して各部分の長さを決める
+    fn read_header(&mut self, events: &mut Vec<PnkEvent>) -> Result<(), PinkError> {
+        if !self.buf.starts_with(&FRAME_MAGIC) && FrameHeader::sniff(&self.buf).is_none() {
+            return Err(PinkError::InvalidFormat);
+        }
+        let header = FrameHeader::read(&self.buf)?;
+        self.cover_len = header
+            .cover_width
+            .checked_mul(header.cover_height)
+            .and_then(|pixels| pixels.checked_mul(4))
+            .ok_or(PinkError::InvalidCover)?;
+        self.payload_len =
+            usize::try_from(header.payload_len).map_err(|_| PinkError::PayloadLengthOverflow)?;
+        self.trailer_len = if header.is_signed() {
+            SIGNATURE_TRAILER_LEN
+        } else {
+            0
+        };
+
+        let info = header.info();
+        self.info = Some(info);
+        self.buf.clear();
+        self.state = FrameState::Extension {
+            remaining: header.extension_len,
+        };
+        events.push(PnkEvent::Header(info));
+        Ok(())
+    }
+}
+
+/// `buf` が `len` バイトになるまで `data` から補い、使ったバイト数を返す
+fn fill(buf: &mut Vec<u8>, data: &[u8], len: usize) -> usize {
+    let n = len.saturating_sub(buf.len()).min(data.len());
+    buf.extend_from_slice(&data[..n]);
+    n
+}
@@ -1,7 +1,157 @@
+use std::io::{self, Cursor, Read};
+
+use js_sys::{Array, Object, Reflect, Uint8Array};
 use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use zip::ZipArchive;
 
+use crate::carrier::decode_carrier;
 use crate::core::{pink072_unwrap, pink072_wrap, pink072_wrap_into};
+use crate::cover::{render_cover_png, CoverStyle};
+use crate::error::PinkError;
+use crate::file::{
+    build_file_payload, build_zip_payload, parse_file_payload, ZipEntry, PAYLOAD_TYPE_FILE,
+    PAYLOAD_TYPE_ZIP,
+};
+use crate::png::{decode_pnk, encode_pnk};
+use crate::seed::Seed9;
+use crate::stream::{PnkDecoder, PnkEvent};
+
+#[wasm_bindgen(typescript_custom_section)]
+const TS_TYPES: &'static str = r#"
+export interface PnkExtract {
+    payloadType: number;
+    fileName: string | null;
+    data: Uint8Array;
+}
+
+export interface FilePayload {
+    fileName: string;
+    data: Uint8Array;
+}
+
+export interface ZipInputEntry {
+    path: string;
+    bytes: Uint8Array;
+}
+
+export interface ZipEntryInfo {
+    path: string;
+    size: number;
+    isDir: boolean;
+}
+
+export type PnkStreamEvent =
+    | {
+          type: "header";
+          version: number;
+          payloadType: number;
+          flags: number;
+          coverWidth: number;
+          coverHeight: number;
+          payloadLength: number;
+      }
+    | { type: "coverReady"; width: number; height: number; rgba: Uint8Array }
+    | { type: "payloadChunk"; data: Uint8Array }
+    | { type: "done" };
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(typescript_type = "PnkExtract")]
+    pub type PnkExtract;
+
+    #[wasm_bindgen(typescript_type = "FilePayload")]
+    pub type FilePayload;
+
+    #[wasm_bindgen(typescript_type = "ZipInputEntry[]")]
+    pub type ZipInputEntries;
+
+    #[wasm_bindgen(typescript_type = "ZipEntryInfo[]")]
+    pub type ZipEntryInfos;
+
+    #[wasm_bindgen(typescript_type = "PnkStreamEvent[]")]
+    pub type PnkStreamEvents;
+}
+
+/// JSに投げるエラー
+///
+/// `code` は `PinkError::code` と同じ安定した識別子。PinkError以外の失敗は
+/// `INVALID_ARGUMENT`（引数が不正）・`UNEXPECTED_PAYLOAD_TYPE`・`INVALID_ZIP`・`IO` になる。
+#[wasm_bindgen(js_name = PinkError)]
+#[derive(Debug)]
+pub struct WasmError {
+    code: &'static str,
+    message: String,
+}
+
+#[wasm_bindgen(js_class = PinkError)]
+impl WasmError {
+    #[wasm_bindgen(getter)]
+    pub fn code(&self) -> String {
+        self.code.to_string()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> String {
+        self.message.clone()
+    }
+
+    #[wasm_bindgen(js_name = toString)]
+    pub fn to_js_string(&self) -> String {
+        format!("PinkError [{}]: {}", self.code, self.message)
+    }
+}
+
+impl WasmError {
+    fn invalid_argument(message: impl Into<String>) -> Self {
+        WasmError {
+            code: "INVALID_ARGUMENT",
+            message: message.into(),
+        }
+    }
+}
+
+impl From<PinkError> for WasmError {
+    fn from(e: PinkError) -> Self {
+        WasmError {
+            code: e.code(),
+            message: e.to_string(),
+        }
+    }
+}
+
+impl From<io::Error> for WasmError {
+    fn from(e: io::Error) -> Self {
+        let code = match e.kind() {
+            io::ErrorKind::InvalidInput => "INVALID_ARGUMENT",
+            io::ErrorKind::InvalidData => "INVALID_FORMAT",
+            _ => "IO",
+        };
+        WasmError {
+            code,
+            message: e.to_string(),
+        }
+    }
+}
+
+impl From<zip::result::ZipError> for WasmError {
+    fn from(e: zip::result::ZipError) -> Self {
+        WasmError {
+            code: "INVALID_ZIP",
+            message: e.to_string(),
+        }
+    }
+}
+
+/// 新しく作ったオブジェクトにプロパティを設定する（失敗しない）
+fn set(object: &Object, key: &str, value: &JsValue) {
+    let _ = Reflect::set(object, &key.into(), value);
+}
 
+/// `wasm_pink072_unwrap` の結果
+///
+/// ペイロードは `intoPayload` で一度だけ取り出す（コピーを繰り返さない）。
 #[wasm_bindgen]
 pub struct UnwrapResult {
     payload_type: u8,
@@ -15,9 +165,15 @@ impl UnwrapResult {
         self.payload_type
     }
 
-    #[wasm_bindgen(getter)]
-    pub fn payload(&self) -> Vec<u8> {
-        self.payload.clone()
+    #[wasm_bindgen(getter, js_name = payloadLength)]
+    pub fn payload_length(&self) -> usize {
+        self.payload.len()
+    }
+
+    /// ペイロードの所有権をJSの `Uint8Array` に移す（この結果オブジェクトは解放される）
+    #[wasm_bindgen(js_name = intoPayload)]
+    pub fn into_payload(self) -> Vec<u8> {
+        self.payload
     }
 }
 
@@ -26,8 +182,9 @@ pub fn wasm_pink072_wrap(
     payload: &[u8],
     payload_type: u8,
     seed9: &[u8],
-) -> Result<Vec<u8>, JsValue> {
-    pink072_wrap(payload, payload_type, seed9).map_err(|e| JsValue::from_str(&e.to_string()))
+) -> Result<Vec<u8>, WasmError> {
+    let seed9 = Seed9::try_from(seed9)?;
+    Ok(pink072_wrap(payload, payload_type, seed9)?)
 }
 
 #[wasm_bindgen]
@@ -36,17 +193,227 @@ pub fn wasm_pink072_wrap_into(
     payload_type: u8,
     seed9: &[u8],
     out_frame: &mut [u8],
-) -> Result<usize, JsValue> {
-    pink072_wrap_into(payload, payload_type, seed9, out_frame)
-        .map_err(|e| JsValue::from_str(&e.to_string()))
+) -> Result<usize, WasmError> {
+    let seed9 = Seed9::try_from(seed9)?;
+    Ok(pink072_wrap_into(payload, payload_type, seed9, out_frame)?)
 }
 
 #[wasm_bindgen]
-pub fn wasm_pink072_unwrap(frame: &[u8]) -> Result<UnwrapResult, JsValue> {
-    let (payload_type, payload) =
-        pink072_unwrap(frame).map_err(|e| JsValue::from_str(&e.to_string()))?;
+pub fn wasm_pink072_unwrap(frame: &[u8]) -> Result<UnwrapResult, WasmError> {
+    let (payload_type, payload) = pink072_unwrap(frame)?;
     Ok(UnwrapResult {
         payload_type,
         payload,
     })
 }
+
+/// フレームをPNK（PNG）にする
+#[wasm_bindgen]
+pub fn wasm_encode_pnk(frame: &[u8]) -> Result<Vec<u8>, WasmError> {
+    Ok(encode_pnk(frame)?)
+}
+
+/// PNKからフレームを取り出す
+#[wasm_bindgen]
+pub fn wasm_decode_pnk(pnk: &[u8]) -> Result<Vec<u8>, WasmError> {
+    Ok(decode_pnk(pnk)?.into_owned())
+}
+
+/// シードのカバーだけを `size` x `size` のPNGとして描画する（`circle` なら円形）
+#[wasm_bindgen]
+pub fn wasm_render_cover_png(seed9: &[u8], size: u32, circle: bool) -> Result<Vec<u8>, WasmError> {
+    let seed9 = Seed9::try_from(seed9)?;
+    let style = if circle {
+        CoverStyle::Circle
+    } else {
+        CoverStyle::Square
+    };
+    Ok(render_cover_png(seed9, size, style)?)
+}
+
+/// ファイル名とデータから `PAYLOAD_TYPE_FILE` のペイロードを構築する
+#[wasm_bindgen]
+pub fn wasm_build_file_payload(file_name: &str, data: &[u8]) -> Result<Vec<u8>, WasmError> {
+    if file_name.len() > u16::MAX as usize {
+        return Err(WasmError::invalid_argument("file name too long"));
+    }
+    Ok(build_file_payload(file_name, data))
+}
+
+/// `PAYLOAD_TYPE_FILE` のペイロードを `{ fileName, data }` に分解する
+#[wasm_bindgen]
+pub fn wasm_parse_file_payload(payload: &[u8]) -> Result<FilePayload, WasmError> {
+    let (file_name, data) = parse_file_payload(payload)?;
+    let result = Object::new();
+    set(&result, "fileName", &file_name.into());
+    set(&result, "data", &Uint8Array::from(data));
+    Ok(result.unchecked_into())
+}
+
+/// PNK（どのキャリア形式でもよい）からペイロードを取り出す
+///
+/// `{ payloadType, fileName, data }` を返す。`fileName` は
+/// `PAYLOAD_TYPE_FILE` の場合だけ文字列で、それ以外は `null`。
+#[wasm_bindgen]
+pub fn wasm_extract_pnk(pnk: &[u8]) -> Result<PnkExtract, WasmError> {
+    let (_, frame) = decode_carrier(pnk)?;
+    let (payload_type, payload) = pink072_unwrap(&frame)?;
+
+    let (file_name, data) = if payload_type == PAYLOAD_TYPE_FILE {
+        let (file_name, data) = parse_file_payload(&payload)?;
+        (JsValue::from(file_name), data)
+    } else {
+        (JsValue::NULL, payload.as_slice())
+    };
+
+    let result = Object::new();
+    set(&result, "payloadType", &payload_type.into());
+    set(&result, "fileName", &file_name);
+    set(&result, "data", &Uint8Array::from(data));
+    Ok(result.unchecked_into())
+}
+
+/// `{ path, bytes }` の配列をメモリ上でZIPにまとめ、PNKにする
+///
+/// `path` は `/` 区切りの相対パス、`bytes` は `Uint8Array`。
+#[wasm_bindgen]
+pub fn wasm_encode_zip_pnk(entries: ZipInputEntries, seed9: &[u8]) -> Result<Vec<u8>, WasmError> {
+    let seed9 = Seed9::try_from(seed9)?;
+    let entries: &Array = entries
+        .dyn_ref()
+        .ok_or_else(|| WasmError::invalid_argument("entries must be an array"))?;
+
+    let mut files = Vec::with_capacity(entries.length() as usize);
+    for entry in entries.iter() {
+        let path = Reflect::get(&entry, &"path".into())
+            .ok()
+            .and_then(|path| path.as_string())
+            .ok_or_else(|| WasmError::invalid_argument("entry path must be a string"))?;
+        let bytes = Reflect::get(&entry, &"bytes".into())
+            .ok()
+            .and_then(|bytes| bytes.dyn_into::<Uint8Array>().ok())
+            .ok_or_else(|| WasmError::invalid_argument("entry bytes must be a Uint8Array"))?
+            .to_vec();
+        files.push((path, bytes));
+    }
+
+    let zip = build_zip_payload(
+        files
+            .iter()
+            .map(|(path, bytes)| (path.as_str(), bytes.as_slice())),
+    )?;
+    let frame = pink072_wrap(&zip, PAYLOAD_TYPE_ZIP, seed9)?;
+    Ok(encode_pnk(&frame)?)
+}
+
+/// ZIPペイロードのPNKを開いたもの
+///
+/// エントリのデータは `entryBytes` で要求されるまで展開しない。
+#[wasm_bindgen]
+pub struct PnkArchive {
+    archive: ZipArchive<Cursor<Vec<u8>>>,
+}
+
+#[wasm_bindgen]
+impl PnkArchive {
+    /// エントリ一覧
+    pub fn entries(&mut self) -> Result<ZipEntryInfos, WasmError> {
+        let entries = Array::new();
+        for i in 0..self.archive.len() {
+            let entry = ZipEntry::of(&self.archive.by_index_raw(i)?);
+            let object = Object::new();
+            set(&object, "path", &entry.path.into());
+            set(&object, "size", &(entry.size as f64).into());
+            set(&object, "isDir", &entry.is_dir.into());
+            entries.push(&object);
+        }
+        Ok(entries.unchecked_into())
+    }
+
+    /// 1エントリのデータを展開して返す
+    #[wasm_bindgen(js_name = entryBytes)]
+    pub fn entry_bytes(&mut self, path: &str) -> Result<Vec<u8>, WasmError> {
+        let mut file = self.archive.by_name(path)?;
+        // 宣言されたサイズは信用できないので、先に確保しない
+        let mut data = Vec::new();
+        file.read_to_end(&mut data)?;
+        Ok(data)
+    }
+}
+
+/// ZIPペイロードのPNK（どのキャリア形式でもよい）を開く
+#[wasm_bindgen]
+pub fn wasm_open_zip_pnk(pnk: &[u8]) -> Result<PnkArchive, WasmError> {
+    let (_, frame) = decode_carrier(pnk)?;
+    let (payload_type, payload) = pink072_unwrap(&frame)?;
+    if payload_type != PAYLOAD_TYPE_ZIP {
+        return Err(WasmError {
+            code: "UNEXPECTED_PAYLOAD_TYPE",
+            message: format!("expected payload type {PAYLOAD_TYPE_ZIP}, got {payload_type}"),
+        });
+    }
+    let archive = ZipArchive::new(Cursor::new(payload))?;
+    Ok(PnkArchive { archive })
+}
+
+/// PNKを少しずつ読むデコーダ（`fetch` の `ReadableStream` 向け）
+#[wasm_bindgen(js_name = PnkDecoder)]
+#[derive(Default)]
+pub struct WasmPnkDecoder {
+    inner: PnkDecoder,
+}
+
+#[wasm_bindgen(js_class = PnkDecoder)]
+impl WasmPnkDecoder {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Self {
+        WasmPnkDecoder::default()
+    }
+
+    /// 次の断片を読み、イベントの配列を返す
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<PnkStreamEvents, WasmError> {
+        let events = Array::new();
+        for event in self.inner.feed(chunk)? {
+            let object = Object::new();
+            match event {
+                PnkEvent::Header(info) => {
+                    set(&object, "type", &"header".into());
+                    set(&object, "version", &info.version.into());
+                    set(&object, "payloadType", &info.payload_type.into());
+                    set(&object, "flags", &info.flags.into());
+                    set(&object, "coverWidth", &info.cover_width.into());
+                    set(&object, "coverHeight", &info.cover_height.into());
+                    set(&object, "payloadLength", &(info.payload_len as f64).into());
+                }
+                PnkEvent::CoverReady {
+                    width,
+                    height,
+                    rgba,
+                } => {
+                    set(&object, "type", &"coverReady".into());
+                    set(&object, "width", &width.into());
+                    set(&object, "height", &height.into());
+                    set(&object, "rgba", &Uint8Array::from(rgba.as_slice()));
+                }
+                PnkEvent::PayloadChunk(data) => {
+                    set(&object, "type", &"payloadChunk".into());
+                    set(&object, "data", &Uint8Array::from(data.as_slice()));
+                }
+                PnkEvent::Done => set(&object, "type", &"done".into()),
+            }
+            events.push(&object);
+        }
+        Ok(events.unchecked_into())
+    }
+
+    /// フレームの終わりまで読んだか
+    #[wasm_bindgen(getter, js_name = isDone)]
+    pub fn is_done(&self) -> bool {
+        self.inner.is_done()
+    }
+
+    /// 入力の終わりで呼び、フレームが揃っていなければ投げる
+    pub fn finish(&self) -> Result<(), WasmError> {
+        Ok(self.inner.finish()?)
+    }
+}
@@ -0,0 +1,154 @@
+//! 最小限のWebPエンコーダ（VP8Lロスレス、変換なし）と終端検出
+
+use alloc::vec::Vec;
+
+use crate::error::PinkError;
+
+/// VP8Lビットストリームのシグネチャ
+const VP8L_SIGNATURE: u8 = 0x2F;
+
+/// 幅・高さの上限（14bit）
+const VP8L_MAX_DIMENSION: usize = 1 << 14;
+
+/// 緑（+ 長さ24種）、赤、青、アルファ、距離の各プレフィックス符号のアルファベットサイズ
+const GREEN_ALPHABET: usize = 256 + 24;
+const CHANNEL_ALPHABET: usize = 256;
+
+/// 符号長符号で符号長を並べる順序
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    17, 18, 0, 1, 2, 3, 4, 5, 16, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15,
+];
+
+/// 任意サイズのRGBAをロスレスWebP（VP8L）にエンコード
+///
+/// 画素をそのまま8bitの固定長符号で並べるだけの単純な形式で、カバーは劣化しない。
+/// 全画素で同じ値のチャンネルは0bitの符号にする。
+/// 幅・高さは 1〜16384（範囲外は `InvalidCover`）。
+pub fn encode_webp_rgba(rgba: &[u8], width: usize, height: usize) -> Result<Vec<u8>, PinkError> {
+    if width == 0 || height == 0 || width > VP8L_MAX_DIMENSION || height > VP8L_MAX_DIMENSION {
+        return Err(PinkError::InvalidCover);
+    }
+    debug_assert_eq!(rgba.len(), width * height * 4);
+
+    // チャンネルごとに、全画素で同じ値ならその値（緑・赤・青・アルファの順）
+    let constant = [1, 0, 2, 3].map(|channel| {
+        let first = rgba[channel];
+        rgba.chunks_exact(4)
+            .all(|pixel| pixel[channel] == first)
+            .then_some(first)
+    });
+    let alpha_is_used = constant[3] != Some(0xFF);
+
+    let mut writer = Vp8lWriter::default();
+    writer.write(VP8L_SIGNATURE as u32, 8);
+    writer.write((width - 1) as u32, 14);
+    writer.write((height - 1) as u32, 14);
+    writer.write(alpha_is_used as u32, 1);
+    writer.write(0, 3); // バージョン
+    writer.write(0, 1); // 変換なし
+    writer.write(0, 1); // カラーキャッシュなし
+    writer.write(0, 1); // メタプレフィックス符号なし
+
+    for (value, alphabet) in constant.iter().zip([
+        GREEN_ALPHABET,
+        CHANNEL_ALPHABET,
+        CHANNEL_ALPHABET,
+        CHANNEL_ALPHABET,
+    ]) {
+        match value {
+            Some(value) => writer.write_single_symbol(*value),
+            None => writer.write_uniform_code(alphabet),
+        }
+    }
+    writer.write_single_symbol(0); // 距離（使わない）
+
+    for pixel in rgba.chunks_exact(4) {
+        for (value, channel) in constant.iter().zip([1, 0, 2, 3]) {
+            if value.is_none() {
+                // 正規ハフマン符号は先頭ビットから読まれるので反転して詰める
+                writer.write(pixel[channel].reverse_bits() as u32, 8);
+            }
+        }
+    }
+
+    let mut bitstream = writer.finish();
+    let chunk_len = bitstream.len();
+    if chunk_len % 2 == 1 {
+        bitstream.push(0);
+    }
+
+    let mut out = Vec::with_capacity(20 + bitstream.len());
+    out.extend_from_slice(b"RIFF");
+    out.extend_from_slice(&((4 + 8 + bitstream.len()) as u32).to_le_bytes());
+    out.extend_from_slice(b"WEBP");
+    out.extend_from_slice(b"VP8L");
+    out.extend_from_slice(&(chunk_len as u32).to_le_bytes());
+    out.extend_from_slice(&bitstream);
+    Ok(out)
+}
+
+/// LSBから詰めるビットライタ
+#[derive(Default)]
+struct Vp8lWriter {
+    out: Vec<u8>,
+    acc: u64,
+    count: u32,
+}
+
+impl Vp8lWriter {
+    fn write(&mut self, bits: u32, len: u32) {
+        self.acc |= (bits as u64) << self.count;
+        self.count += len;
+        while self.count >= 8 {
+            self.out.push(self.acc as u8);
+            self.acc >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// 1シンボルだけの単純符号（読み出しに0bitを使う）
+    fn write_single_symbol(&mut self, symbol: u8) {
+        self.write(1, 1); // 単純符号
+        self.write(0, 1); // シンボル数 - 1
+        self.write(1, 1); // シンボルは8bit
+        self.write(symbol as u32, 8);
+    }
+
+    /// 先頭256シンボルがすべて符号長8の正規符号（残りは未使用）
+    fn write_uniform_code(&mut self, alphabet: usize) {
+        self.write(0, 1); // 通常の符号
+
+        // 符号長符号: 符号長0と8だけをそれぞれ1bitで表す
+        let used = CODE_LENGTH_ORDER.iter().position(|&len| len == 8).unwrap() + 1;
+        self.write((used - 4) as u32, 4);
+        for &len in &CODE_LENGTH_ORDER[..used] {
+            self.write(matches!(len, 0 | 8) as u32, 3);
+        }
+        self.write(0, 1); // 最大シンボル数はアルファベットサイズ
+        for symbol in 0..alphabet {
+            self.write((symbol < 256) as u32, 1);
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.out.push(self.acc as u8);
+        }
+        self.out
+    }
+}
+
+/// WebPの終端（RIFFコンテナの直後）を探す
+///
+/// RIFFヘッダのサイズ欄から求める。
+pub fn find_webp_end(data: &[u8]) -> Result<usize, PinkError> {
+    if data.len() < 12 || &data[0..4] != b"RIFF" || &data[8..12] != b"WEBP" {
+        return Err(PinkError::InvalidFormat);
+    }
+    let riff_len = u32::from_le_bytes([data[4], data[5], data[6], data[7]]) as u64;
+    let end = 8 + riff_len + (riff_len & 1);
+    if end > data.len() as u64 {
+        return Err(PinkError::MissingImageEnd);
+    }
+    Ok(end as usize)
+}
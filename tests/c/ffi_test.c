@@ -0,0 +1,95 @@
+/* C ABIのテスト（tests/ffi.rs がビルドして実行する）
+ * 引数: 作業用ディレクトリ */
+#include <stdio.h>
+#include <string.h>
+
+#include "pink072.h"
+
+#define CHECK(cond)                                                        \
+    do {                                                                   \
+        if (!(cond)) {                                                     \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__,         \
+                    __LINE__, #cond);                                      \
+            return 1;                                                      \
+        }                                                                  \
+    } while (0)
+
+static const uint8_t SEED[9] = {1, 2, 3, 4, 5, 6, 7, 8, 9};
+
+int main(int argc, char **argv) {
+    if (argc < 2) {
+        fprintf(stderr, "usage: %s WORK_DIR\n", argv[0]);
+        return 2;
+    }
+
+    /* フレーム -> PNK -> フレーム -> ペイロード */
+    const char *message = "hello from C";
+    Pink072Buffer frame, pnk, decoded, payload;
+    uint8_t payload_type = 0xFF;
+
+    CHECK(pink072_wrap((const uint8_t *)message, strlen(message), 1, SEED, &frame) == PINK072_OK);
+    CHECK(pink072_encode_pnk(frame.data, frame.len, &pnk) == PINK072_OK);
+    CHECK(pnk.len > frame.len);
+    CHECK(pink072_decode_pnk(pnk.data, pnk.len, &decoded) == PINK072_OK);
+    CHECK(decoded.len == frame.len && memcmp(decoded.data, frame.data, frame.len) == 0);
+    CHECK(pink072_unwrap(decoded.data, decoded.len, &payload_type, &payload) == PINK072_OK);
+    CHECK(payload_type == 1);
+    CHECK(payload.len == strlen(message) && memcmp(payload.data, message, payload.len) == 0);
+
+    /* エラーコード */
+    Pink072Buffer unused;
+    CHECK(pink072_decode_pnk((const uint8_t *)"not a png", 9, &unused) == PINK072_ERR_INVALID_FORMAT);
+    CHECK(pink072_unwrap(frame.data, 40, &payload_type, &unused) == PINK072_ERR_FRAME_TOO_SMALL);
+    CHECK(pink072_encode_pnk(frame.data, 10, &unused) == PINK072_ERR_FRAME_TOO_SMALL);
+    CHECK(pink072_wrap(NULL, 1, 0, SEED, &unused) == PINK072_ERR_NULL_POINTER);
+    CHECK(pink072_wrap(NULL, 0, 0, SEED, NULL) == PINK072_ERR_NULL_POINTER);
+    CHECK(strcmp(pink072_error_message(PINK072_ERR_TRUNCATED_FRAME), "truncated frame") == 0);
+    CHECK(strcmp(pink072_error_message(PINK072_ERR_ENCRYPTION_FAILED), "failed to encrypt the payload") == 0);
+    CHECK(strcmp(pink072_error_message(-1), "unknown error") == 0);
+
+    pink072_buffer_free(frame);
+    pink072_buffer_free(pnk);
+    pink072_buffer_free(decoded);
+    pink072_buffer_free(payload);
+
+    /* ファイル単位 */
+    char input[1024], output[1024], out_dir[1024], restored[1024];
+    snprintf(input, sizeof input, "%s/note.txt", argv[1]);
+    snprintf(output, sizeof output, "%s/note.pnk", argv[1]);
+    snprintf(out_dir, sizeof out_dir, "%s/out", argv[1]);
+    snprintf(restored, sizeof restored, "%s/out/note.txt", argv[1]);
+
+    FILE *f = fopen(input, "wb");
+    CHECK(f != NULL);
+    fputs(message, f);
+    fclose(f);
+
+    CHECK(pink072_encode_file(input, output, SEED) == PINK072_OK);
+    CHECK(pink072_decode_file(output, out_dir) == PINK072_OK);
+    CHECK(pink072_decode_file(input, out_dir) == PINK072_ERR_INVALID_FORMAT);
+
+    /* 末尾が欠けたPNKはフレームのエラーコードになる */
+    char truncated[1024];
+    snprintf(truncated, sizeof truncated, "%s/truncated.pnk", argv[1]);
+    f = fopen(output, "rb");
+    CHECK(f != NULL);
+    static uint8_t pnk_data[1 << 16];
+    size_t pnk_len = fread(pnk_data, 1, sizeof pnk_data, f);
+    fclose(f);
+    CHECK(pnk_len > 1 && pnk_len < sizeof pnk_data);
+    f = fopen(truncated, "wb");
+    CHECK(f != NULL);
+    fwrite(pnk_data, 1, pnk_len - 1, f);
+    fclose(f);
+    CHECK(pink072_decode_file(truncated, out_dir) == PINK072_ERR_TRUNCATED_FRAME);
+
+    char buffer[64] = {0};
+    f = fopen(restored, "rb");
+    CHECK(f != NULL);
+    size_t read = fread(buffer, 1, sizeof buffer - 1, f);
+    fclose(f);
+    CHECK(read == strlen(message) && memcmp(buffer, message, read) == 0);
+
+    puts("ok");
+    return 0;
+}
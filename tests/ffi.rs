@@ -0,0 +1,98 @@
+//! C ABIのテスト（`cargo test --features ffi`）
+//!
+//! `tests/c/ffi_test.c` を `include/pink072.h` と共有ライブラリに対して
+//! コンパイルして実行する。Cコンパイラは環境変数 `CC`（無ければ `cc`）。
+#![cfg(all(feature = "ffi", unix))]
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const MANIFEST_DIR: &str = env!("CARGO_MANIFEST_DIR");
+
+/// 共有ライブラリをビルドし、そのディレクトリを返す
+///
+/// クレートは `rlib` なので `cargo rustc --crate-type cdylib` でビルドする。外側の
+/// `cargo test` とビルドのロックを取り合わないよう、別のターゲットディレクトリを使う。
+fn build_library() -> PathBuf {
+    let target_dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("cdylib");
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let status = Command::new(cargo)
+        .args([
+            "rustc",
+            "--lib",
+            "--crate-type",
+            "cdylib",
+            "--features",
+            "ffi",
+        ])
+        .arg("--manifest-path")
+        .arg(Path::new(MANIFEST_DIR).join("Cargo.toml"))
+        .env("CARGO_TARGET_DIR", &target_dir)
+        .status()
+        .expect("run cargo rustc");
+    assert!(status.success(), "failed to build the cdylib");
+    target_dir.join("debug")
+}
+
+#[test]
+fn c_program_round_trips_through_the_abi() {
+    let work_dir = env::temp_dir().join("pink072_test_ffi");
+    let _ = fs::remove_dir_all(&work_dir);
+    fs::create_dir_all(&work_dir).unwrap();
+
+    let lib_dir = build_library();
+    let program = work_dir.join("ffi_test");
+    let compiler = env::var("CC").unwrap_or_else(|_| "cc".to_string());
+    let status = Command::new(compiler)
+        .arg(Path::new(MANIFEST_DIR).join("tests/c/ffi_test.c"))
+        .arg("-I")
+        .arg(Path::new(MANIFEST_DIR).join("include"))
+        .arg("-L")
+        .arg(&lib_dir)
+        .arg("-lpink072")
+        .arg("-o")
+        .arg(&program)
+        .status()
+        .expect("run the C compiler");
+    assert!(status.success(), "failed to compile ffi_test.c");
+
+    let output = Command::new(&program)
+        .arg(&work_dir)
+        .env("LD_LIBRARY_PATH", &lib_dir)
+        .env("DYLD_LIBRARY_PATH", &lib_dir)
+        .output()
+        .expect("run ffi_test");
+    assert!(
+        output.status.success(),
+        "ffi_test failed:\n{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "ok");
+
+    let _ = fs::remove_dir_all(&work_dir);
+}
+
+#[test]
+fn header_declares_every_export() {
+    let source = fs::read_to_string(Path::new(MANIFEST_DIR).join("src/ffi.rs")).unwrap();
+    let header = fs::read_to_string(Path::new(MANIFEST_DIR).join("include/pink072.h")).unwrap();
+
+    for line in source.lines() {
+        if let Some(rest) = line
+            .strip_prefix("pub unsafe extern \"C\" fn ")
+            .or_else(|| line.strip_prefix("pub extern \"C\" fn "))
+        {
+            let name = &rest[..rest.find('(').unwrap()];
+            assert!(
+                header.contains(&format!("{name}(")),
+                "{name} is not declared"
+            );
+        } else if let Some(rest) = line.strip_prefix("pub const ") {
+            let (name, value) = rest.split_once(": i32 = ").unwrap();
+            let define = format!("#define {name} {}", value.trim_end_matches(';'));
+            assert!(header.contains(&define), "missing `{define}`");
+        }
+    }
+}
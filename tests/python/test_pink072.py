@@ -0,0 +1,83 @@
+"""Pythonバインディングのテスト
+
+    maturin develop && pytest tests/python
+"""
+
+import pytest
+
+import pink072
+
+SEED = bytes(range(1, 10))
+
+
+def test_wrap_round_trips():
+    frame = pink072.wrap(b"hello", pink072.PAYLOAD_TYPE_RAW, SEED)
+    assert frame[:4] == b"PINK"
+    assert pink072.unwrap(frame) == (pink072.PAYLOAD_TYPE_RAW, b"hello")
+
+
+def test_seed_accepts_strings():
+    frame = pink072.wrap(b"hello", pink072.PAYLOAD_TYPE_RAW, SEED.hex())
+    assert frame == pink072.wrap(b"hello", pink072.PAYLOAD_TYPE_RAW, SEED)
+
+
+def test_pnk_round_trips():
+    frame = pink072.wrap(b"hello", pink072.PAYLOAD_TYPE_RAW, SEED)
+    pnk = pink072.encode_pnk(frame)
+    assert pnk[:8] == b"\x89PNG\r\n\x1a\n"
+    assert pink072.decode_pnk(pnk) == frame
+
+
+def test_errors_carry_codes():
+    frame = pink072.wrap(b"hello", pink072.PAYLOAD_TYPE_RAW, SEED)
+    with pytest.raises(pink072.PinkError) as info:
+        pink072.unwrap(frame[:-1])
+    assert info.value.code == "TRUNCATED_FRAME"
+    assert isinstance(info.value, ValueError)
+
+    with pytest.raises(pink072.PinkError) as info:
+        pink072.wrap(b"hello", pink072.PAYLOAD_TYPE_RAW, b"short")
+    assert info.value.code == "SEED_LENGTH"
+
+    with pytest.raises(pink072.PinkError) as info:
+        pink072.encode_pnk(b"not a frame")
+    assert info.value.code in ("FRAME_TOO_SMALL", "INVALID_FORMAT")
+
+
+def test_files_round_trip_and_scan(tmp_path):
+    source = tmp_path / "note.txt"
+    source.write_bytes(b"hello, file")
+    pnk = tmp_path / "note.pnk"
+    pink072.encode_file(source, pnk, SEED)
+
+    out = tmp_path / "out"
+    out.mkdir()
+    assert pink072.decode_auto(pnk, out) == ["note.txt"]
+    assert (out / "note.txt").read_bytes() == b"hello, file"
+
+    found = pink072.scan(tmp_path)
+    assert len(found) == 1
+    entry = found[0]
+    assert entry["format"] == "png"
+    assert entry["payload_type"] == pink072.PAYLOAD_TYPE_FILE
+    assert entry["file_name"] == "note.txt"
+    assert entry["intact"]
+
+
+def test_file_errors(tmp_path):
+    broken = tmp_path / "broken.pnk"
+    broken.write_bytes(b"\x89PNG\r\n\x1a\n")
+    with pytest.raises(pink072.PinkError) as info:
+        pink072.decode_auto(broken, tmp_path)
+    assert info.value.code
+
+    # フレームは正しいが、ファイルのペイロードが壊れている
+    bad_payload = tmp_path / "bad_payload.pnk"
+    frame = pink072.wrap(b"x", pink072.PAYLOAD_TYPE_FILE, SEED)
+    bad_payload.write_bytes(pink072.encode_pnk(frame))
+    with pytest.raises(pink072.PinkError) as info:
+        pink072.decode_auto(bad_payload, tmp_path)
+    assert info.value.code == "INVALID_FORMAT"
+
+    with pytest.raises(OSError):
+        pink072.decode_auto(tmp_path / "missing.pnk", tmp_path)
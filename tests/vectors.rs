@@ -0,0 +1,167 @@
+//! `test-vectors/` の適合性テスト
+//!
+//! 他の言語の実装も同じ `manifest.json` とファイルで検証する。
+//! ベクタは `cargo run --example gen_test_vectors` で再生成できる。
+#![cfg(feature = "alloc")]
+
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
+use pink072::{
+    decode_pnk, encode_pnk, encode_pnk_with_layout, pink072_unwrap, pink072_unwrap_strict,
+    pink072_wrap, pink072_wrap_with_cover, sign_frame, verify_frame, Keyring, PinkError, PnkLayout,
+    Seed9, SigningKey, COVER_LEN, FLAG_EXTERNAL_COVER, HEADER_LEN,
+};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+fn root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("test-vectors")
+}
+
+fn manifest() -> Value {
+    let text = fs::read_to_string(root().join("manifest.json")).expect("read manifest.json");
+    let manifest: Value = serde_json::from_str(&text).expect("parse manifest.json");
+    assert_eq!(manifest["format"], "pink072-test-vectors");
+    assert_eq!(manifest["version"], 1);
+    manifest
+}
+
+fn cases<'a>(manifest: &'a Value, key: &str) -> &'a [Value] {
+    let cases = manifest[key].as_array().expect("case list");
+    assert!(!cases.is_empty(), "no {key} cases");
+    cases
+}
+
+fn str_field<'a>(case: &'a Value, key: &str) -> &'a str {
+    case[key]
+        .as_str()
+        .unwrap_or_else(|| panic!("{}: missing {key}", case["name"]))
+}
+
+fn read(case: &Value, key: &str) -> Vec<u8> {
+    fs::read(root().join(str_field(case, key))).expect("read vector file")
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn unhex(text: &str) -> Vec<u8> {
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).expect("hex"))
+        .collect()
+}
+
+fn u32_at(frame: &[u8], at: usize) -> u32 {
+    u32::from_le_bytes(frame[at..at + 4].try_into().unwrap())
+}
+
+/// カバーの範囲: 拡張領域の後ろから幅 x 高さ x 4 バイト（旧形式は常に72x72）
+fn cover_range(frame: &[u8]) -> Range<usize> {
+    if !frame.starts_with(b"PINK") {
+        return HEADER_LEN..HEADER_LEN + COVER_LEN;
+    }
+    let start = HEADER_LEN + u32_at(frame, 24) as usize;
+    let len = if frame[7] & FLAG_EXTERNAL_COVER != 0 {
+        u32_at(frame, 16) as usize * u32_at(frame, 20) as usize * 4
+    } else {
+        COVER_LEN
+    };
+    start..start + len
+}
+
+#[test]
+fn valid_vectors_round_trip() {
+    let manifest = manifest();
+    for case in cases(&manifest, "valid") {
+        let name = str_field(case, "name");
+        let seed9: Option<Seed9> = case["seed"].as_str().map(|s| s.parse().expect("seed"));
+        let payload_type = case["payload_type"].as_u64().expect("payload_type") as u8;
+        let payload = read(case, "payload");
+        let frame = read(case, "frame");
+        let signing_key = case["signing_key"].as_str().map(|key| {
+            SigningKey::from_bytes(&unhex(key).try_into().expect("32-byte signing key"))
+        });
+
+        if !case["decode_only"].as_bool().expect("decode_only") {
+            let mut wrapped = match case.get("cover") {
+                Some(_) => pink072_wrap_with_cover(
+                    &payload,
+                    payload_type,
+                    &read(case, "cover"),
+                    u32_at(&frame, 16),
+                    u32_at(&frame, 20),
+                ),
+                None => pink072_wrap(&payload, payload_type, seed9.expect("seed")),
+            }
+            .expect("wrap");
+            if let Some(signing_key) = &signing_key {
+                wrapped = sign_frame(&wrapped, signing_key).expect("sign");
+            }
+            assert!(wrapped == frame, "{name}: frame bytes differ");
+        }
+        assert_eq!(
+            pink072_unwrap(&frame).expect("unwrap"),
+            (payload_type, payload.clone()),
+            "{name}"
+        );
+        match seed9 {
+            Some(seed9) => assert!(pink072_unwrap_strict(&frame, seed9).is_ok(), "{name}"),
+            None => assert_eq!(
+                pink072_unwrap_strict(&frame, [0u8; 9]),
+                Err(PinkError::CoverMismatch),
+                "{name}"
+            ),
+        }
+        if let Some(signing_key) = &signing_key {
+            let keyring: Keyring = [signing_key.verifying_key()].into_iter().collect();
+            let key_id = verify_frame(&frame, &keyring).expect("verify");
+            assert_eq!(key_id.to_string(), str_field(case, "key_id"), "{name}");
+        }
+
+        let cover = &frame[cover_range(&frame)];
+        assert_eq!(
+            hex(&Sha256::digest(cover)),
+            str_field(case, "cover_sha256"),
+            "{name}"
+        );
+
+        let pnk = read(case, "pnk");
+        let pnk_chunk = read(case, "pnk_chunk");
+        assert!(
+            encode_pnk(&frame).expect("encode") == pnk,
+            "{name}: pnk bytes differ"
+        );
+        assert!(
+            encode_pnk_with_layout(&frame, PnkLayout::Chunk).expect("encode chunk") == pnk_chunk,
+            "{name}: chunk pnk bytes differ"
+        );
+        assert!(
+            decode_pnk(&pnk).expect("decode") == frame.as_slice(),
+            "{name}"
+        );
+        assert!(
+            decode_pnk(&pnk_chunk).expect("decode chunk") == frame.as_slice(),
+            "{name}"
+        );
+    }
+}
+
+#[test]
+fn invalid_vectors_fail_with_expected_code() {
+    let manifest = manifest();
+    for case in cases(&manifest, "invalid") {
+        let name = str_field(case, "name");
+        let data = read(case, "file");
+        let result = match str_field(case, "input") {
+            "frame" => pink072_unwrap(&data),
+            "pnk" => decode_pnk(&data).and_then(|frame| pink072_unwrap(&frame)),
+            input => panic!("{name}: unknown input kind {input}"),
+        };
+        let err = result.expect_err(name);
+        assert_eq!(err.code(), str_field(case, "error"), "{name}");
+    }
+}